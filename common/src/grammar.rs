@@ -8,24 +8,47 @@ pub type ProdVec = SmallVec<[u32; 4]>;
 #[serde(rename_all = "snake_case")]
 pub enum Assoc { Left, Right, NoAssoc }
 
-// previously I support state/act just like lex/flex, later I found they are not necessary in my application and removed them
-//
 // we are using str, not String here, because in most of my application we work with borrowed string
 // if you need to dynamically generate strings and add them to RawGrammar, you can use a typed_arena::Arena to store them
 #[derive(Deserialize)]
 pub struct RawGrammar<'a> {
   pub include: &'a str,
   pub epilogue: Option<&'a str>,
-  pub priority: Vec<RawPriorityRow<'a>>,
+  // read the lexer part of the grammar (all fields of RawLexer) from another toml file, which can also be used by parser-macros
+  // the path is relative to this file, and the lexer part should not appear in this file
+  // it is resolved by the reader of the toml file, e.g., `parser_gen` and `build::Builder`, not by `extend`
+  #[serde(default)] pub lex_path: Option<&'a str>,
+  #[serde(default)] pub priority: Vec<RawPriorityRow<'a>>,
+  // named regex, `{NAME}` in lexical rules (and other definitions) is replaced by `(regex)`
+  #[serde(default)] pub definitions: IndexMap<&'a str, Cow<'a, str>>,
   // map re to term
   // K must be Cow<str>, because sometimes we have to write escape chars in the key string
   // so the key may not be a borrow from the input string
   // but we can always avoid escape chars in the value string
-  pub lexical: IndexMap<Cow<'a, str>, &'a str>,
+  #[serde(default)] pub lexical: IndexMap<Cow<'a, str>, &'a str>,
+  // lexer modes (start conditions in lex/flex), `lexical` above is the rules of the initial mode `_Init`
+  // an entry named `_Init` can only contain mode transitions for the rules in `lexical`
+  #[serde(default)] pub lexer_mode: IndexMap<&'a str, RawLexerMode<'a>>,
   // this string should contain full field definition, e.g.: "a: u32, b: u32,"
   #[serde(default)] pub lexer_field: &'a str,
   // run before Lexer::next() returns
   #[serde(default)] pub lexer_action: &'a str,
+  // count columns in code points instead of bytes (the input should be utf-8)
+  #[serde(default)] pub unicode: bool,
+  // a tab advances the column to the next tab stop, None means a tab is just one column
+  #[serde(default)] pub tab_width: Option<u32>,
+  // synthesize layout tokens from the indentation at line starts, see RawIndent
+  #[serde(default)] pub indent: Option<RawIndent<'a>>,
+  // map a term to the terms it can be reinterpreted as, e.g., a contextual keyword `Async = ['Id']`
+  // when an LR parser has no action on the lexed token, it tries these terms in order, and uses the first one that has an action
+  #[serde(default)] pub fallback: IndexMap<&'a str, Vec<&'a str>>,
+  // map term name to its value type and conversion, see RawTermValue
+  #[serde(default)] pub term_value: IndexMap<&'a str, RawTermValue<'a>>,
+  // the error type returned by fallible actions (see RawProductionRhs::fallible), only Rust supports it
+  // if it is set, `parse` returns `ParseError`, which is either a syntax error token or a user error
+  #[serde(default)] pub error_ty: Option<&'a str>,
+  // track the `Span` of each symbol, actions can use `@$` (lhs) and `@n` (the n-th rhs symbol), see `Placeholder`
+  #[serde(default)] pub locations: bool,
   #[serde(default)] pub parser_field: &'a str,
   pub start: &'a str,
   pub production: Vec<RawProduction<'a>>,
@@ -39,10 +62,50 @@ pub const START_NT_NAME: &str = "_";
 pub const EPS: &str = "_Eps";
 pub const EOF: &str = "_Eof";
 pub const ERR: &str = "_Err";
+pub const INIT_MODE: &str = "_Init";
 pub const EPS_IDX: usize = 0;
 pub const EOF_IDX: usize = 1;
 pub const ERR_IDX: usize = 2;
 
+// the lexer part of RawGrammar, the fields have the same meaning as those in RawGrammar
+// it is the `lex` of parser-macros, or the file at `RawGrammar::lex_path`
+#[derive(Deserialize, Default)]
+pub struct RawLexer<'a> {
+  #[serde(borrow)]
+  pub priority: Vec<RawPriorityRow<'a>>,
+  #[serde(default)] pub definitions: IndexMap<&'a str, Cow<'a, str>>,
+  pub lexical: IndexMap<Cow<'a, str>, &'a str>,
+  #[serde(default)] pub lexer_mode: IndexMap<&'a str, RawLexerMode<'a>>,
+  #[serde(default)] pub lexer_field: &'a str,
+  #[serde(default)] pub lexer_action: &'a str,
+  #[serde(default)] pub unicode: bool,
+  #[serde(default)] pub tab_width: Option<u32>,
+  #[serde(default)] pub indent: Option<RawIndent<'a>>,
+  #[serde(default)] pub fallback: IndexMap<&'a str, Vec<&'a str>>,
+  #[serde(default)] pub term_value: IndexMap<&'a str, RawTermValue<'a>>,
+  #[serde(default)] pub error_ty: Option<&'a str>,
+  #[serde(default)] pub locations: bool,
+}
+
+impl<'a> RawLexer<'a> {
+  // replace the lexer part of `g` with `self`
+  pub fn apply(self, g: &mut RawGrammar<'a>) {
+    g.priority = self.priority;
+    g.definitions = self.definitions;
+    g.lexical = self.lexical;
+    g.lexer_mode = self.lexer_mode;
+    g.lexer_field = self.lexer_field;
+    g.lexer_action = self.lexer_action;
+    g.unicode = self.unicode;
+    g.tab_width = self.tab_width;
+    g.indent = self.indent;
+    g.fallback = self.fallback;
+    g.term_value = self.term_value;
+    g.error_ty = self.error_ty;
+    g.locations = self.locations;
+  }
+}
+
 #[derive(Deserialize)]
 pub struct RawPriorityRow<'a> {
   pub assoc: Assoc,
@@ -50,6 +113,42 @@ pub struct RawPriorityRow<'a> {
   pub terms: Vec<&'a str>,
 }
 
+#[derive(Deserialize)]
+pub struct RawLexerMode<'a> {
+  #[serde(borrow, default)]
+  pub lexical: IndexMap<Cow<'a, str>, &'a str>,
+  // map re to mode name, re must be a key in `lexical` of this mode
+  // after the token is matched, the lexer pushes / switches (replaces the top of mode stack) to that mode
+  #[serde(default)] pub push: IndexMap<Cow<'a, str>, &'a str>,
+  #[serde(default)] pub switch: IndexMap<Cow<'a, str>, &'a str>,
+  // after the token is matched, the lexer returns to the previous mode (it stays in `_Init` if the stack is empty)
+  #[serde(default)] pub pop: Vec<Cow<'a, str>>,
+}
+
+// the term names of the layout tokens, they are added to terms automatically
+// when the first token of a line is more indented than the current level, `indent` is emitted before it
+// when it is less indented, one `dedent` is emitted for each level it closes, followed by `_Err` if it doesn't match any level
+// `newline` is emitted at the end of each non-blank line, and all of them are emitted before `_Eof`
+// the lines inside brackets (`open` and `close` are the term names of them) are joined, i.e., no layout tokens are emitted
+#[derive(Deserialize)]
+pub struct RawIndent<'a> {
+  pub indent: &'a str,
+  pub dedent: &'a str,
+  pub newline: &'a str,
+  #[serde(borrow, default)]
+  pub open: Vec<&'a str>,
+  #[serde(default)] pub close: Vec<&'a str>,
+}
+
+// a term with value type `ty` is converted when it is shifted, and actions receive the value instead of the Token
+// `conv` is an expression of type `Result<ty, _>`, and it can use `piece` (the bytes of the token)
+// when it returns `Err`, the parser reports a `_Err` token with the same piece and location
+#[derive(Deserialize)]
+pub struct RawTermValue<'a> {
+  pub ty: &'a str,
+  pub conv: &'a str,
+}
+
 #[derive(Deserialize)]
 pub struct RawProduction<'a> {
   pub lhs: &'a str,
@@ -66,6 +165,89 @@ pub struct RawProductionRhs<'a> {
   pub rhs_arg: Option<Vec<(&'a str, &'a str)>>,
   pub act: &'a str,
   pub prec: Option<&'a str>,
+  // the action returns `Result<ty, error_ty>` instead of `ty`, and `Err` aborts parsing
+  #[serde(default)] pub fallible: bool,
+}
+
+// a placeholder in action code, like bison: `None` is lhs (`$$`/`@$`), `Some(n)` is the n-th rhs symbol, starting from 1 (`$n`/`@n`)
+// `$name`/`@name` is also resolved to `Some(n)` if `name` is in rhs, where it must appear exactly once, otherwise it is left as is (e.g., java `$tmp`)
+// `Val` is the value of the symbol, `Loc` is its location (only when `RawGrammar::locations` is on)
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Placeholder { Val(Option<usize>), Loc(Option<usize>) }
+
+// the literals in action code, whose contents are skipped when finding placeholders
+// `C` (c, c++, java): `"..."` & `'...'`; `Rust`: `"..."` & char literals like `'"'`, but not lifetimes like `'a`
+// `Script` (python, typescript, go): `"..."`, `'...'` & `` `...` ``, the last has no escapes
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Quotes { C, Rust, Script }
+
+// the end of the literal starting with `quote` at `s[i]`, escapes are skipped if `escape` is true
+fn lit_end(s: &[u8], i: usize, quote: u8, escape: bool) -> usize {
+  let mut i = i + 1;
+  while i < s.len() && s[i] != quote { i += if escape && s[i] == b'\\' { 2 } else { 1 }; }
+  i
+}
+
+// find all placeholders in `act`, whose rhs symbols are `rhs`, return (start, end, placeholder), where `act[start..end]` is the placeholder
+// `@` is only recognized when `loc` is true, and the literals of `quotes` are skipped
+pub fn placeholders(act: &str, rhs: &[&str], loc: bool, quotes: Quotes) -> Result<Vec<(usize, usize, Placeholder)>, String> {
+  let (s, mut ret, mut i) = (act.as_bytes(), Vec::new(), 0);
+  while i < s.len() {
+    match s[i] {
+      b'"' => i = lit_end(s, i, b'"', true),
+      b'\'' if quotes != Quotes::Rust => i = lit_end(s, i, b'\'', true),
+      b'`' if quotes == Quotes::Script => i = lit_end(s, i, b'`', false),
+      b'\'' => match act[i + 1..].chars().next() {
+        Some('\\') => i = lit_end(s, i, b'\'', true),
+        Some(ch) if s.get(i + 1 + ch.len_utf8()) == Some(&b'\'') => i += 1 + ch.len_utf8(),
+        _ => {} // a lifetime
+      }
+      ch @ b'$' | ch @ b'@' if (ch == b'$' || loc) && i + 1 < s.len() => {
+        let rest = &s[i + 1..];
+        let (len, idx) = if rest[0] == b'$' { (1, None) } else if rest[0].is_ascii_digit() {
+          let len = rest.iter().take_while(|ch| ch.is_ascii_digit()).count();
+          match act[i + 1..i + 1 + len].parse::<usize>() {
+            Ok(n) if n != 0 && n <= rhs.len() => (len, Some(n)),
+            _ => return Err(format!("`{}` is out of range, there are {} rhs symbols", &act[i..i + 1 + len], rhs.len())),
+          }
+        } else if rest[0].is_ascii_alphabetic() || rest[0] == b'_' {
+          let len = rest.iter().take_while(|ch| ch.is_ascii_alphanumeric() || **ch == b'_').count();
+          let name = &act[i + 1..i + 1 + len];
+          let mut pos = rhs.iter().enumerate().filter(|(_, &x)| x == name).map(|(j, _)| j + 1);
+          match (pos.next(), pos.next()) {
+            (Some(n), None) => (len, Some(n)),
+            (Some(_), Some(_)) => return Err(format!("`{}` is ambiguous, \"{}\" appears more than once in rhs", &act[i..i + 1 + len], name)),
+            (None, _) => {
+              i += 1 + len;
+              continue;
+            }
+          }
+        } else {
+          i += 1;
+          continue;
+        };
+        ret.push((i, i + 1 + len, if ch == b'$' { Placeholder::Val(idx) } else { Placeholder::Loc(idx) }));
+        i += len;
+      }
+      _ => {}
+    }
+    i += 1;
+  }
+  Ok(ret)
+}
+
+// replace all placeholders in `act` with the result of `f`, the placeholders should have been checked by `placeholders`
+pub fn replace_placeholders<'a>(act: &'a str, rhs: &[&str], loc: bool, quotes: Quotes, mut f: impl FnMut(Placeholder) -> String) -> Cow<'a, str> {
+  let ph = placeholders(act, rhs, loc, quotes).expect("invalid placeholder");
+  if ph.is_empty() { return Cow::Borrowed(act); }
+  let (mut ret, mut last) = (String::with_capacity(act.len()), 0);
+  for (start, end, p) in ph {
+    ret.push_str(&act[last..start]);
+    ret.push_str(&f(p));
+    last = end;
+  }
+  ret.push_str(&act[last..]);
+  Cow::Owned(ret)
 }
 
 // note: EPS/EOF/ERR's contents are not valid variable names
@@ -77,10 +259,10 @@ pub fn validate_variable_name(s: &str) -> bool {
   }
 }
 
-// input: the two field in RawGrammar(or constructed in other ways)
+// input: the `priority` and `indent` field in RawGrammar(or constructed in other ways), and all lexical rules
 // return: (Vec<(term, pri_assoc)>, term2id)
-fn parse_term<'a>(priority: &'a [RawPriorityRow], lexical: &'a IndexMap<Cow<'a, str>, &'a str>, validate_name: bool) -> Result<(Vec<Term<'a>>, HashMap<&'a str, u32>), String> {
-  let mut terms = vec![Term { name: EPS, pri_assoc: None }, Term { name: EOF, pri_assoc: None }, Term { name: ERR, pri_assoc: None }];
+fn parse_term<'a>(priority: &'a [RawPriorityRow], lexical: &[LexRule<'a>], indent: Option<&RawIndent<'a>>, validate_name: bool) -> Result<(Vec<Term<'a>>, HashMap<&'a str, u32>), String> {
+  let mut terms = vec![Term { name: EPS, pri_assoc: None, value: None }, Term { name: EOF, pri_assoc: None, value: None }, Term { name: ERR, pri_assoc: None, value: None }];
   let mut term2id = HashMap::default();
   term2id.insert(EPS, 0);
   term2id.insert(EOF, 1);
@@ -95,24 +277,65 @@ fn parse_term<'a>(priority: &'a [RawPriorityRow], lexical: &'a IndexMap<Cow<'a,
         return Err(format!("duplicate term when assigning priority: \"{}\"", name));
       } else {
         term2id.insert(name, terms.len() as u32);
-        terms.push(Term { name, pri_assoc: Some(pri_assoc) });
+        terms.push(Term { name, pri_assoc: Some(pri_assoc), value: None });
       }
     }
   }
 
-  for (_, &name) in lexical {
+  let layout = indent.iter().flat_map(|x| vec![x.indent, x.dedent, x.newline]);
+  for name in lexical.iter().map(|x| x.term).chain(layout) {
     if name != EOF && name != ERR && name != EPS && validate_name && !validate_variable_name(name) {
       return Err(format!("term is not a valid variable name: \"{}\"", name));
     }
     term2id.entry(name).or_insert_with(|| {
       let id = terms.len() as u32;
-      terms.push(Term { name, pri_assoc: None });
+      terms.push(Term { name, pri_assoc: None, value: None });
       id
     });
   }
+  if let Some(indent) = indent {
+    for &name in indent.open.iter().chain(indent.close.iter()) {
+      if !term2id.contains_key(name) { return Err(format!("bracket term for indentation undefined: \"{}\"", name)); }
+    }
+  }
   Ok((terms, term2id))
 }
 
+// input: the `lexical` and `lexer_mode` field in RawGrammar
+// return: (all lexical rules, mode names), rules of the same mode are continuous, and the first mode is always INIT_MODE
+fn parse_lexical<'a>(lexical: &'a IndexMap<Cow<'a, str>, &'a str>, lexer_mode: &'a IndexMap<&'a str, RawLexerMode<'a>>) -> Result<(Vec<LexRule<'a>>, Vec<&'a str>), String> {
+  let mut mode = vec![INIT_MODE];
+  for (&name, raw) in lexer_mode {
+    if name == INIT_MODE {
+      if !raw.lexical.is_empty() { return Err(format!("lexer mode \"{}\" cannot define rules, they should be in `lexical`", INIT_MODE)); }
+    } else if !validate_variable_name(name) {
+      return Err(format!("lexer mode is not a valid variable name: \"{}\"", name));
+    } else { mode.push(name); }
+  }
+  let mut rules = Vec::new();
+  for (idx, &name) in mode.iter().enumerate() {
+    let lexical = if idx == 0 { lexical } else { &lexer_mode[name].lexical };
+    let start = rules.len();
+    for (re, &term) in lexical {
+      rules.push(LexRule { re: &**re, term, mode: idx as u32, trans: ModeTrans::Stay });
+    }
+    if let Some(raw) = lexer_mode.get(name) {
+      let rules = &mut rules[start..];
+      let mut set = |re: &Cow<str>, trans: ModeTrans| match rules.iter_mut().find(|x| x.re == &**re) {
+        Some(rule) if rule.trans == ModeTrans::Stay => { rule.trans = trans; Ok(()) }
+        Some(_) => Err(format!("lexer mode \"{}\" has multiple transitions on re \"{}\"", name, re)),
+        None => Err(format!("lexer mode \"{}\" has transition on undefined re \"{}\"", name, re)),
+      };
+      let find = |to: &str| mode.iter().position(|&x| x == to).map(|x| x as u32)
+        .ok_or_else(|| format!("lexer mode \"{}\" transfers to undefined mode \"{}\"", name, to));
+      for (re, to) in &raw.push { set(re, ModeTrans::Push(find(to)?))?; }
+      for (re, to) in &raw.switch { set(re, ModeTrans::Switch(find(to)?))?; }
+      for re in &raw.pop { set(re, ModeTrans::Pop)?; }
+    }
+  }
+  Ok((rules, mode))
+}
+
 // terminal id is distributed in [0, terms.len())
 // non-terminal id is distributed in [terms.len(), terms.len() + nt.len())
 // there are 3 fixed terminal id: EPS_IDX, EOF_IDX, ERR_IDX (of course they are in [0, terms.len()))
@@ -121,11 +344,29 @@ pub struct Grammar<'a> {
   pub terms: Vec<Term<'a>>,
   pub nt: Vec<NonTerm<'a>>,
   pub prod: Vec<Prod<'a>>,
+  // rules of all lexer modes, the dfa accepting state refers to index in it
+  pub lexical: Vec<LexRule<'a>>,
+  // mode names, mode id is the index in it, lexer starts at mode 0 (INIT_MODE)
+  pub lexer_mode: Vec<&'a str>,
+  // (term, the terms it can fall back to), see `RawGrammar::fallback`
+  pub fallback: Vec<(u32, Vec<u32>)>,
 }
 
+pub struct LexRule<'a> {
+  pub re: &'a str,
+  pub term: &'a str,
+  pub mode: u32,
+  pub trans: ModeTrans,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ModeTrans { Stay, Push(u32), Switch(u32), Pop }
+
 pub struct Term<'a> {
   pub name: &'a str,
   pub pri_assoc: Option<(u32, Assoc)>,
+  // (type, conversion), see RawTermValue
+  pub value: Option<(&'a str, &'a str)>,
 }
 
 pub struct NonTerm<'a> {
@@ -145,6 +386,8 @@ pub struct Prod<'a> {
   // index in prod
   pub id: u32,
   pub pri: Option<u32>,
+  // see RawProductionRhs::fallible
+  pub fallible: bool,
 }
 
 impl RawGrammar<'_> {
@@ -152,7 +395,20 @@ impl RawGrammar<'_> {
   // if `validate_name == true`, will call `validate_variable_name` to check every token's name
   // otherwise those names will not be checked
   pub fn extend(&mut self, validate_name: bool) -> Result<Grammar, String> {
-    let (terms, term2id) = parse_term(&self.priority, &self.lexical, validate_name)?;
+    let (lexical, lexer_mode) = parse_lexical(&self.lexical, &self.lexer_mode)?;
+    let (mut terms, term2id) = parse_term(&self.priority, &lexical, self.indent.as_ref(), validate_name)?;
+    for (&name, value) in &self.term_value {
+      match term2id.get(name) {
+        Some(&t) if t as usize > ERR_IDX => terms[t as usize].value = Some((value.ty, value.conv)),
+        Some(_) => return Err(format!("term cannot have a value: \"{}\"", name)),
+        None => return Err(format!("term_value uses undefined term: \"{}\"", name)),
+      }
+    }
+    let term = |name: &str| term2id.get(name).copied().ok_or_else(|| format!("fallback uses undefined term: \"{}\"", name));
+    let mut fallback = Vec::with_capacity(self.fallback.len());
+    for (&from, to) in &self.fallback {
+      fallback.push((term(from)?, to.iter().map(|&x| term(x)).collect::<Result<_, _>>()?));
+    }
     let mut nt = Vec::new();
     let mut nt2id = HashMap::default();
 
@@ -166,7 +422,7 @@ impl RawGrammar<'_> {
     self.production.push(RawProduction {
       lhs: START_NT_NAME,
       ty: "", // won't be used
-      rhs: vec![RawProductionRhs { rhs: vec![self.start], act: "_1", rhs_arg: None, prec: None }],
+      rhs: vec![RawProductionRhs { rhs: vec![self.start], act: "_1", rhs_arg: None, prec: None, fallible: false }],
     });
 
     for (idx, prod) in self.production.iter().enumerate() {
@@ -217,7 +473,10 @@ impl RawGrammar<'_> {
             Some(&t) => prod_pri = terms[t as usize].pri_assoc.map(|(pri, _)| pri),
           }
         }
-        lhs_prod.push(Prod { rhs: prod_rhs, act: &rhs.act, args: rhs.rhs_arg.as_ref(), lhs, id: 0, pri: prod_pri });
+        if rhs.fallible && self.error_ty.is_none() {
+          return Err(format!("production \"{} -> {}\" is fallible, but `error_ty` is not specified", raw_prod.lhs, rhs.rhs.join(" ")));
+        }
+        lhs_prod.push(Prod { rhs: prod_rhs, act: &rhs.act, args: rhs.rhs_arg.as_ref(), lhs, id: 0, pri: prod_pri, fallible: rhs.fallible });
 
         // type checking
         if let Some(rhs_arg) = &rhs.rhs_arg {
@@ -234,9 +493,16 @@ impl RawGrammar<'_> {
                     raw_prod.lhs, rhs.rhs.join(" "), rhs_tk, nt_ty, rhs_ty));
                 }
               }
-              (_, Some(_)) => if !rhs_ty.starts_with("Token") { // maybe user will use some lifetime specifier
-                return Err(format!("production \"{} -> {}\" rhs and method arguments have conflict signature: `{}` requires Token, while method takes `{}`",
-                  raw_prod.lhs, rhs.rhs.join(" "), rhs_tk, rhs_ty));
+              (_, Some(&t)) => match terms[t as usize].value {
+                // the type may come from token stream (e.g., `Vec < u8 >`), so whitespaces are ignored
+                Some((ty, _)) => if !ty.chars().filter(|x| !x.is_whitespace()).eq(rhs_ty.chars().filter(|x| !x.is_whitespace())) {
+                  return Err(format!("production \"{} -> {}\" rhs and method arguments have conflict signature: `{}` requires `{}`, while method takes `{}`",
+                    raw_prod.lhs, rhs.rhs.join(" "), rhs_tk, ty, rhs_ty));
+                }
+                None => if !rhs_ty.starts_with("Token") { // maybe user will use some lifetime specifier
+                  return Err(format!("production \"{} -> {}\" rhs and method arguments have conflict signature: `{}` requires Token, while method takes `{}`",
+                    raw_prod.lhs, rhs.rhs.join(" "), rhs_tk, rhs_ty));
+                }
               }
               _ => {} // unreachable, because checked above
             }
@@ -251,7 +517,7 @@ impl RawGrammar<'_> {
     }
     let mut prod = prod.into_iter().flat_map(|x| x.into_iter()).collect::<Vec<_>>();
     for (idx, prod) in prod.iter_mut().enumerate() { prod.id = idx as u32; }
-    Ok(Grammar { raw: self, nt, terms, prod })
+    Ok(Grammar { raw: self, nt, terms, prod, lexical, lexer_mode, fallback })
   }
 }
 
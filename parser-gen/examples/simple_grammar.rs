@@ -15,7 +15,7 @@ fn parse_lines(s: &str) -> Result<RawGrammar, String> {
       return Err(format!("invalid token name: \"{}\"", START_NT_NAME));
     }
     all_lhs.insert(lhs);
-    production.push(RawProduction { lhs, ty: "", rhs: vec![RawProductionRhs { rhs, rhs_arg: None, act: "", prec: None }] });
+    production.push(RawProduction { lhs, ty: "", rhs: vec![RawProductionRhs { rhs, rhs_arg: None, act: "", prec: None, fallible: false }] });
   }
   let start = production.get(0).ok_or_else(|| "grammar must have at least one production rule".to_owned())?.lhs;
   let mut lexical = IndexMap::default();
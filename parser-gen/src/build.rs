@@ -0,0 +1,155 @@
+// generate parsers in `build.rs`, so that crates on stable Rust can use the generator without parser-macros, e.g.:
+// parser_gen::build::Builder::new("src/grammar.toml").algo(PGAlgo::LALR1).lang(Lang::Rs).generate().unwrap();
+// then `include!(concat!(env!("OUT_DIR"), "/grammar.rs"));` in the crate
+use crate::*;
+use std::{env, fs, io, path::{Path, PathBuf}};
+
+// what to do with the conflicts found when generating the parser
+#[derive(Eq, PartialEq, Copy, Clone)]
+pub enum ConflictPolicy {
+  Ignore,
+  // print them as cargo warnings
+  Warn,
+  // print them as cargo warnings, and return an error (after the code is generated), so that `unwrap` fails the build
+  Deny,
+}
+
+pub struct Builder {
+  path: PathBuf,
+  algo: PGAlgo,
+  lang: Lang,
+  out_dir: Option<PathBuf>,
+  out_name: Option<String>,
+  conflict: ConflictPolicy,
+  use_unsafe: bool,
+  recursive_descent: bool,
+  direct_lr: bool,
+  direct_lexer: bool,
+  prefix: String,
+  package: String,
+  template: String,
+}
+
+// forward everything to `Config`, but collect the conflicts and lexical warnings, so that they can be handled by `ConflictPolicy`
+struct Collect<'a, W> {
+  cfg: Config<'a, W>,
+  conflicts: Vec<String>,
+  warnings: Vec<String>,
+}
+
+impl<W: Write> Codegen for Collect<'_, W> {
+  fn lexical_warning(&mut self, reason: String) { self.warnings.push(reason); }
+
+  fn dfa(&mut self, dfa: &Dfa) -> Result<()> { self.cfg.dfa(dfa) }
+
+  fn lang(&self) -> Lang { self.cfg.lang }
+
+  fn ll(&mut self, g: &Grammar, ll: LLCtx, dfa: &Dfa) -> Result<()> {
+    self.conflicts.extend(show_ll::conflict(&ll.table, g));
+    self.cfg.ll(g, ll, dfa)
+  }
+
+  fn lr1(&mut self, g: &Grammar, lr1: &Lr1Fsm, dfa: &Dfa, orig_table: Table, table: Table, conflict: Vec<Conflict>) -> Result<()> {
+    self.conflicts.extend(show_lr::conflict(g, &conflict));
+    self.cfg.lr1(g, lr1, dfa, orig_table, table, conflict)
+  }
+}
+
+// cargo only recognizes single line messages
+fn cargo_warning(msg: &str) {
+  for line in msg.lines() { println!("cargo:warning={}", line); }
+}
+
+fn invalid(path: &Path, e: impl Display) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, format!("invalid grammar toml \"{}\": {}", path.display(), e))
+}
+
+impl Builder {
+  // by default: LALR(1), Rust, `ConflictPolicy::Warn`, and the output is "{OUT_DIR}/{file stem of path}.{extension of lang}"
+  pub fn new(path: impl Into<PathBuf>) -> Builder {
+    Builder {
+      path: path.into(),
+      algo: PGAlgo::LALR1,
+      lang: Lang::Rs,
+      out_dir: None,
+      out_name: None,
+      conflict: ConflictPolicy::Warn,
+      use_unsafe: false,
+      recursive_descent: false,
+      direct_lr: false,
+      direct_lexer: false,
+      prefix: String::new(),
+      package: "parser".to_owned(),
+      template: String::new(),
+    }
+  }
+
+  pub fn algo(mut self, algo: PGAlgo) -> Builder { (self.algo = algo, self).1 }
+  pub fn lang(mut self, lang: Lang) -> Builder { (self.lang = lang, self).1 }
+  pub fn out_dir(mut self, out_dir: impl Into<PathBuf>) -> Builder { (self.out_dir = Some(out_dir.into()), self).1 }
+  pub fn out_name(mut self, out_name: impl Into<String>) -> Builder { (self.out_name = Some(out_name.into()), self).1 }
+  pub fn conflict(mut self, conflict: ConflictPolicy) -> Builder { (self.conflict = conflict, self).1 }
+  pub fn use_unsafe(mut self, use_unsafe: bool) -> Builder { (self.use_unsafe = use_unsafe, self).1 }
+  pub fn recursive_descent(mut self, recursive_descent: bool) -> Builder { (self.recursive_descent = recursive_descent, self).1 }
+  pub fn direct_lr(mut self, direct_lr: bool) -> Builder { (self.direct_lr = direct_lr, self).1 }
+  pub fn direct_lexer(mut self, direct_lexer: bool) -> Builder { (self.direct_lexer = direct_lexer, self).1 }
+  // see `Config::prefix`, `Config::package` and `Config::template`
+  pub fn prefix(mut self, prefix: impl Into<String>) -> Builder { (self.prefix = prefix.into(), self).1 }
+  pub fn package(mut self, package: impl Into<String>) -> Builder { (self.package = package.into(), self).1 }
+  pub fn template(mut self, template: impl Into<String>) -> Builder { (self.template = template.into(), self).1 }
+
+  // generate the code, and return the path of the output file
+  // `cargo:rerun-if-changed` is printed for the grammar toml and its `lex_path`
+  pub fn generate(self) -> Result<PathBuf> {
+    let input = fs::read_to_string(&self.path)?;
+    println!("cargo:rerun-if-changed={}", self.path.display());
+    let lex;
+    let mut raw = toml::from_str::<RawGrammar>(&input).map_err(|e| invalid(&self.path, e))?;
+    if let Some(lex_path) = raw.lex_path {
+      let lex_path = self.path.parent().unwrap_or_else(|| Path::new("")).join(lex_path);
+      lex = fs::read_to_string(&lex_path)?;
+      println!("cargo:rerun-if-changed={}", lex_path.display());
+      toml::from_str::<RawLexer>(&lex).map_err(|e| invalid(&lex_path, e))?.apply(&mut raw);
+    }
+    let out_dir = match self.out_dir {
+      Some(x) => x,
+      None => env::var_os("OUT_DIR").map(PathBuf::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "`out_dir` is not set, and OUT_DIR is not defined (not running in build.rs?)"))?,
+    };
+    let out = out_dir.join(match self.out_name {
+      Some(x) => x,
+      None => {
+        let stem = self.path.file_stem().and_then(|x| x.to_str()).unwrap_or("parser");
+        let ext = match self.lang {
+          Lang::Rs => "rs", Lang::Cpp => "cpp", Lang::Java => "java", Lang::C => "c", Lang::Python => "py",
+          Lang::TypeScript => "ts", Lang::Go => "go", Lang::Json => "json", Lang::Template => "txt",
+        };
+        format!("{}.{}", stem, ext)
+      }
+    });
+    let mut gen = Collect {
+      cfg: Config {
+        use_unsafe: self.use_unsafe,
+        recursive_descent: self.recursive_descent,
+        direct_lr: self.direct_lr,
+        direct_lexer: self.direct_lexer,
+        prefix: &self.prefix,
+        package: &self.package,
+        template: &self.template,
+        ..Config::new(self.lang, BufWriter::new(File::create(&out)?))
+      },
+      conflicts: Vec::new(),
+      warnings: Vec::new(),
+    };
+    work(raw, self.algo, &mut gen)?;
+    gen.cfg.code_output.flush()?;
+    if self.conflict != ConflictPolicy::Ignore {
+      for w in gen.warnings.iter().chain(&gen.conflicts) { cargo_warning(w); }
+    }
+    if self.conflict == ConflictPolicy::Deny && !gen.conflicts.is_empty() {
+      return Err(io::Error::new(io::ErrorKind::InvalidData,
+        format!("{} conflict(s) in grammar \"{}\":\n{}", gen.conflicts.len(), self.path.display(), gen.conflicts.join("\n"))));
+    }
+    Ok(out)
+  }
+}
@@ -0,0 +1,105 @@
+use crate::*;
+
+// C has no `u8` like types, map `fmt::min_u` to the `stdint.h` ones
+fn min_u(x: usize) -> String { format!("uint{}_t", &fmt::min_u(x)[1..]) }
+
+impl<W: std::io::Write> Config<'_, W> {
+  // the code to run when ACTION has no entry for the token, like `fmt::lr_error`, but all names need the prefix
+  fn c_error<'a>(g: &'a Grammar, prefix: &'a str) -> impl Display + 'a {
+    fmt_::fn2display(move |f| {
+      let name = |t: u32| g.terms[t as usize].name;
+      if !g.fallback.is_empty() {
+        f.write_str("switch(token.kind){")?;
+        for (from, to) in &g.fallback {
+          write!(f, "case {}{}:", prefix, name(*from))?;
+          for &x in to {
+            write!(f, "if((ACTION[state][{0}{1}]&3)!=3){{token.kind={0}{1};continue;}}", prefix, name(x))?;
+          }
+          f.write_str("break;")?;
+        }
+        f.write_str("default:break;}")?;
+      }
+      f.write_str("*err=token;return 1;")
+    })
+  }
+
+  // values live in the union `Value`, the field of type i is `_i`, and `tag` records which field is active
+  // the tags are checked by `assert` unless `use_unsafe`
+  fn c_act<'a>(g: &'a Grammar, types2id: HashMap<&'a str, u32>, prefix: &'a str, use_unsafe: bool) -> impl Display + 'a {
+    fmt_::fn2display(move |f| {
+      for (i, prod) in g.prod.iter().enumerate() {
+        write!(f, "case {}:{{", i)?;
+        for (j, &x) in prod.rhs.iter().enumerate().rev() {
+          let name = fmt_::fn2display(move |f|
+            match prod.args { Some(args) => f.write_str(args[j].0), None => write!(f, "_{}", j + 1) });
+          let (ty, field, tag) = match g.as_nt(x) {
+            Some(x) => {
+              let id = types2id[g.nt[x].ty];
+              (g.nt[x].ty.to_owned(), format!("_{}", id), id + 1)
+            }
+            None => (format!("{}Token", prefix), "_Token".to_owned(), 0),
+          };
+          if !use_unsafe { write!(f, "assert(stk[top].tag=={});", tag)?; }
+          writeln!(f, "{} {}=stk[top--].value.{};(void){};", ty, name, field, name)?;
+        }
+        let lhs = g.nt[prod.lhs as usize].ty;
+        let id = types2id[lhs];
+        writeln!(f, "{} __;{}value._{}=__;tag={};break;}}", lhs,
+          if i == g.prod.len() - 1 { "__=_1;".into() } else { fmt::act(g, prod, Lang::C) }, id, id + 1)?;
+      }
+      Ok(())
+    })
+  }
+
+  pub fn c_lalr1(&mut self, g: &Grammar, table: &Table, dfa: &Dfa) -> Result<()> {
+    let (types, types2id) = fmt::gather_types(g);
+    let (prefix, res_type) = (self.prefix, g.nt.last().unwrap().ty);
+    let header = format!(
+      include_str!("template/lalr1.h.template"),
+      guard = format!("{}PARSER_H", prefix.to_uppercase()),
+      include = g.raw.include,
+      prefix = prefix,
+      token_kind = fmt::comma_sep(g.terms.iter().map(|x| format!("{}{}", prefix, x.name))),
+      lexer_field = fmt::lexer_field(g, Lang::C),
+      value_field = fmt_::fn2display(|f| {
+        for (i, ty) in types.iter().enumerate() { write!(f, "{} _{};", ty, i)?; }
+        Ok(())
+      }),
+      // an empty struct is not standard C
+      parser_field = if g.raw.parser_field.is_empty() { "char _unused;" } else { g.raw.parser_field },
+      res_type = res_type,
+    );
+    match &mut self.header_output {
+      Some((name, w)) => {
+        w.write_all(header.as_bytes())?;
+        writeln!(self.code_output, "#include \"{}\"", name)?;
+      }
+      None => self.code_output.write_all(header.as_bytes())?,
+    }
+    let res_id = types2id[res_type];
+    write!(
+      self.code_output, include_str!("template/lalr1.c.template"),
+      prefix = prefix,
+      acc = fmt::acc(g, dfa, prefix),
+      ec = fmt::comma_sep(dfa.ec.iter()),
+      u_dfa_size = min_u(dfa.nodes.len()),
+      ec_num = dfa.ec_num,
+      dfa_edge = fmt::dfa_edge(dfa, ('{', '}')),
+      lexer_action = g.raw.lexer_action,
+      col_step = fmt::col_step(g, Lang::C),
+      u_lr_fsm_size = min_u(table.len()),
+      u_act_size = min_u(table.len() * 4),
+      res_type = res_type,
+      prod = fmt::comma_sep(g.prod.iter().map(|x| x.lhs)),
+      term_num = g.terms.len(),
+      nt_num = g.nt.len(),
+      action = fmt::action(g, table, ('{', '}')),
+      goto = fmt::goto(g, table, ('{', '}')),
+      parser_act = Self::c_act(g, types2id, prefix, self.use_unsafe),
+      res_check = if self.use_unsafe { String::new() } else { format!("assert(stk[top].tag == {});", res_id + 1) },
+      res_id = res_id,
+      on_error = Self::c_error(g, prefix),
+      epilogue = g.raw.epilogue.unwrap_or("")
+    )
+  }
+}
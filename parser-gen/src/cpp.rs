@@ -1,44 +1,96 @@
 use crate::*;
 
 impl<W: std::io::Write> Config<'_, W> {
-  pub fn cpp_lalr1(&mut self, g: &Grammar, table: &Table, dfa: &Dfa) -> Result<()> {
-    let (types, _) = fmt::gather_types(g);
-    let parse_res = g.nt.last().unwrap().ty;
+  fn cpp_common(&mut self, g: &Grammar, dfa: &Dfa, types: &[&str]) -> Result<()> {
+    let (parse_res, direct_lexer) = (g.nt.last().unwrap().ty, self.direct_lexer);
     write!(
-      self.code_output, include_str!("template/lalr1.cpp.template"),
+      self.code_output, include_str!("template/common.cpp.template"),
       include = g.raw.include,
       token_kind = fmt::comma_sep(g.terms.iter().map(|x| x.name)),
       stack_item = types.join(","),
-      lexer_field = g.raw.lexer_field,
-      acc = fmt::acc(g, dfa, "Token"),
-      ec = fmt::comma_sep(dfa.ec.iter()),
-      u_dfa_size = fmt::min_u(dfa.nodes.len()),
-      ec_num = dfa.ec_num,
-      dfa_edge = fmt::dfa_edge(dfa, ('{', '}')),
+      lexer_field = fmt::lexer_field(g, Lang::Cpp),
+      mode_start = fmt::mode_start(g, Lang::Cpp),
+      next_name = fmt::next_name(g),
+      layout = fmt::layout(g, Lang::Cpp),
+      mode_decl = fmt::mode_last_state(g, Lang::Cpp).0,
+      mode_acc = fmt::mode_last_state(g, Lang::Cpp).1,
+      mode_trans = fmt::mode_trans(g, dfa, Lang::Cpp),
+      dfa_table = fmt_::fn2display(move |f| if direct_lexer { Ok(()) } else {
+        writeln!(f, "const static Token::Kind ACC[] = {{{}}};", fmt::acc(g, dfa, "Token::"))?;
+        writeln!(f, "const static u8 EC[] = {{{}}};", fmt::comma_sep(dfa.ec.iter()))?;
+        write!(f, "const static {} DFA_EDGE[][{}] = {{{}}};", fmt::min_u(dfa.nodes.len()), dfa.ec_num, fmt::dfa_edge(dfa, ('{', '}')))
+      }),
+      dfa_next = fmt_::fn2display(move |f| if direct_lexer { write!(f, "{}", fmt::dfa_next_code(dfa, Lang::Cpp)) } else {
+        f.write_str("nxt = DFA_EDGE[state][EC[ch]];")
+      }),
+      dfa_acc = fmt_::fn2display(move |f| if direct_lexer { write!(f, "{}", fmt::dfa_acc_code(g, dfa, Lang::Cpp)) } else {
+        f.write_str("acc = ACC[nxt];")
+      }),
       lexer_action = g.raw.lexer_action,
+      col_step = fmt::col_step(g, Lang::Cpp),
       parser_struct = fmt_::fn2display(move |f| if g.raw.parser_def.is_none() {
         writeln!(f, r"struct Parser{{std::variant<{},Token>parse(Lexer&lexer);{}}};", parse_res, g.raw.parser_field)
       } else { Ok(()) }),
+    )
+  }
+
+  // is_pair == true: `stk` is std::vector<std::pair<StackItem, integer>>; is_pair == false: `stk` is std::vector<StackItem>
+  fn cpp_act<'a>(g: &'a Grammar, is_pair: bool) -> impl Display + 'a {
+    fmt_::fn2display(move |f| {
+      let first = if is_pair { ".first" } else { "" };
+      for (i, prod) in g.prod.iter().enumerate() {
+        write!(f, "case {}:{{", i)?;
+        for (j, &x) in prod.rhs.iter().enumerate().rev() {
+          let name = fmt_::fn2display(move |f|
+            match prod.args { Some(args) => f.write_str(args[j].0), None => write!(f, "_{}", j + 1) });
+          let ty = if let Some(x) = g.as_nt(x) { g.nt[x].ty } else { "Token" };
+          writeln!(f, "[[maybe_unused]]{1} {}(std::move(*std::get_if<{1}>(&stk.back(){2})));stk.pop_back();", name, ty, first)?;
+        }
+        writeln!(f, "{}break;}}", if i == g.prod.len() - 1 { "__=std::move(_1);".into() } else { fmt::act(g, prod, Lang::Cpp) })?;
+      }
+      Ok(())
+    })
+  }
+
+  pub fn cpp_lalr1(&mut self, g: &Grammar, table: &Table, dfa: &Dfa) -> Result<()> {
+    let (types, _) = fmt::gather_types(g);
+    self.cpp_common(g, dfa, &types)?;
+    write!(
+      self.code_output, include_str!("template/lalr1.cpp.template"),
       u_lr_fsm_size = fmt::min_u(table.len()),
       u_act_size = fmt::min_u(table.len() * 4),
       parser_type = g.raw.parser_def.unwrap_or("Parser"),
-      res_type = parse_res,
+      res_type = g.nt.last().unwrap().ty,
       prod = fmt::comma_sep(g.prod.iter().map(|x| x.lhs)),
       term_num = g.terms.len(),
       nt_num = g.nt.len(),
       action = fmt::action(g, table, ('{', '}')),
       goto = fmt::goto(g, table, ('{', '}')),
-      parser_act = fmt_::fn2display(|f| (for (i, prod) in g.prod.iter().enumerate() {
-        write!(f, "case {}:{{", i)?;
-        for (j, &x) in prod.rhs.iter().enumerate().rev() {
-          let name = fmt_::fn2display(move |f|
-            match prod.args { Some(args) => f.write_str(args[j].0), None => write!(f, "_{}", j + 1) });
-          let ty = if let Some(x) = g.as_nt(x) { g.nt[x].ty } else { "Token" };
-          writeln!(f, "[[maybe_unused]]{1} {}(std::move(*std::get_if<{1}>(&stk.back().first)));stk.pop_back();", name, ty)?;
-        }
-        writeln!(f, "{}break;}}", if i == g.prod.len() - 1 { "__=std::move(_1);" } else { prod.act })?;
-      }, Ok(())).1),
+      parser_act = Self::cpp_act(g, true),
+      on_error = fmt::lr_error(g, Lang::Cpp),
+      epilogue = g.raw.epilogue.unwrap_or("")
+    )
+  }
+
+  pub fn cpp_ll1(&mut self, g: &Grammar, ll: &LLCtx, dfa: &Dfa) -> Result<()> {
+    let (types, _) = fmt::gather_types(g);
+    self.cpp_common(g, dfa, &types)?;
+    let (rhs, rhs_start) = fmt::ll_rhs(g);
+    write!(
+      self.code_output, include_str!("template/ll1.cpp.template"),
+      term_num = g.terms.len(),
+      token_num = g.token_num(),
+      follow = fmt::ll_follow(g, ll, ('{', '}')),
+      parser_type = g.raw.parser_def.unwrap_or("Parser"),
+      res_type = g.nt.last().unwrap().ty,
+      u_prod_size = fmt::min_u(g.prod.len() + 1),
+      table = fmt::ll_table(g, ll, ('{', '}')),
+      u_token_num = fmt::min_u(g.token_num()),
+      rhs = rhs,
+      u_rhs_size = fmt::min_u(g.prod.iter().map(|x| x.rhs.len()).sum()),
+      rhs_start = rhs_start,
+      parser_act = Self::cpp_act(g, false),
       epilogue = g.raw.epilogue.unwrap_or("")
     )
   }
-}
\ No newline at end of file
+}
@@ -0,0 +1,60 @@
+// expand references to named definitions `{NAME}` in regex, the definition is wrapped in parentheses
+// `{` inside [], escaped `\{` and the braces of unicode class `\p{..}` are not references
+use common::IndexMap;
+use std::borrow::Cow;
+
+type Defs<'d> = IndexMap<&'d str, Cow<'d, str>>;
+
+// return the length of NAME if `s` starts with NAME followed by '}'
+fn ref_name(s: &[u8]) -> Option<usize> {
+  match s.first() { Some(ch) if ch.is_ascii_alphabetic() || *ch == b'_' => {} _ => return None }
+  let len = s.iter().position(|&ch| !(ch.is_ascii_alphanumeric() || ch == b'_'))?;
+  if s[len] == b'}' { Some(len) } else { None }
+}
+
+// `stack` is the definitions being expanded, for cycle detection; `used` collects all expanded definitions
+fn expand_to<'d>(re: &str, defs: &'d Defs<'d>, stack: &mut Vec<&'d str>, used: &mut Vec<&'d str>, out: &mut String) -> Result<(), String> {
+  let (bytes, mut i, mut last, mut in_class) = (re.as_bytes(), 0, 0, false);
+  while i < bytes.len() {
+    match bytes[i] {
+      b'\\' => {
+        i += match (bytes.get(i + 1), bytes.get(i + 2)) {
+          (Some(b'p'), Some(b'{')) | (Some(b'P'), Some(b'{')) => re[i..].find('}').map(|x| x + 1).unwrap_or(re.len() - i),
+          _ => 2,
+        };
+        continue;
+      }
+      b'[' if !in_class => in_class = true,
+      b']' if in_class => in_class = false,
+      b'{' if !in_class => if let Some(len) = ref_name(&bytes[i + 1..]) {
+        let name = &re[i + 1..i + 1 + len];
+        let (_, &name, def) = defs.get_full(name).ok_or_else(|| format!("undefined definition \"{}\"", name))?;
+        if let Some(pos) = stack.iter().position(|&x| x == name) {
+          return Err(format!("definition cycle: {} -> {}", stack[pos..].join(" -> "), name));
+        }
+        if !used.contains(&name) { used.push(name); }
+        out.push_str(&re[last..i]);
+        out.push('(');
+        stack.push(name);
+        expand_to(def, defs, stack, used, out)?;
+        stack.pop();
+        out.push(')');
+        i += len + 2;
+        last = i;
+        continue;
+      }
+      _ => {}
+    }
+    i += 1;
+  }
+  out.push_str(&re[last.min(re.len())..]);
+  Ok(())
+}
+
+// return (expanded regex, names of the expanded definitions)
+pub fn expand<'a, 'd>(re: &'a str, defs: &'d Defs<'d>) -> Result<(Cow<'a, str>, Vec<&'d str>), String> {
+  if defs.is_empty() || !re.contains('{') { return Ok((Cow::Borrowed(re), Vec::new())); }
+  let (mut out, mut used) = (String::with_capacity(re.len()), Vec::new());
+  expand_to(re, defs, &mut Vec::new(), &mut used, &mut out)?;
+  Ok((if used.is_empty() { Cow::Borrowed(re) } else { Cow::Owned(out) }, used))
+}
@@ -1,74 +1,473 @@
 use crate::*;
+use std::borrow::Cow;
 
 #[inline(always)]
 pub fn comma_sep<'a, T: Display + 'a>(it: impl Iterator<Item=T> + Clone + 'a) -> impl Display + 'a {
   fmt_::sep(it, ",")
 }
 
+// rewrite the placeholders (see `Placeholder`) in the action of `prod` to the variables of each language
+// rust: `$n` is `_n`, `$$` is `__` (then the action is wrapped to return `__`), `@$` & `@n` are `_loc` & `_locn` (see `rs::loc_bind`)
+// c++ & c & python & typescript & go: `$n` is `_n`, `$$` is `__`; java: `$n` is `$n`, `$$` is `$.$`
+// if the arguments are named (by parser-macros), `$n` is the name of the n-th argument
+pub fn act<'a>(g: &Grammar, prod: &'a Prod, lang: Lang) -> Cow<'a, str> {
+  let rhs = prod.rhs.iter().map(|&x| g.show_token(x as _)).collect::<Vec<_>>();
+  let mut lhs_val = false;
+  let act = replace_placeholders(prod.act, &rhs, g.raw.locations, lang.quotes(), |p| match (p, lang) {
+    (Placeholder::Val(Some(n)), _) if prod.args.is_some() => prod.args.unwrap()[n - 1].0.to_owned(),
+    (Placeholder::Val(Some(n)), Lang::Java) => format!("${}", n),
+    (Placeholder::Val(Some(n)), _) => format!("_{}", n),
+    (Placeholder::Val(None), Lang::Java) => "$.$".to_owned(),
+    (Placeholder::Val(None), _) => {
+      lhs_val = true;
+      "__".to_owned()
+    }
+    (Placeholder::Loc(None), _) => "_loc".to_owned(),
+    (Placeholder::Loc(Some(n)), _) => format!("_loc{}", n),
+  });
+  if lhs_val && lang == Lang::Rs { Cow::Owned(format!("let __;{{{}}}__", act)) } else { act }
+}
+
 #[inline(always)]
 pub fn min_u(x: usize) -> &'static str {
   // I don't think any number beyond `u32` is possible
   match x { 0..=255 => "u8", 256..=65535 => "u16", _ => "u32" }
 }
 
+// the value types of terms (see `RawTermValue`) are after the types of non-terminals
 pub fn gather_types<'a>(g: &Grammar<'a>) -> (Vec<&'a str>, HashMap<&'a str, u32>) {
   let mut types = Vec::new();
   let mut types2id = HashMap::default();
-  for nt in &g.nt {
-    types2id.entry(nt.ty).or_insert_with(|| {
+  for ty in g.nt.iter().map(|x| x.ty).chain(g.terms.iter().filter_map(|x| x.value.map(|v| v.0))) {
+    types2id.entry(ty).or_insert_with(|| {
       let id = types.len() as u32;
-      types.push(nt.ty);
+      types.push(ty);
       id
     });
   }
   (types, types2id)
 }
 
-pub fn acc<'a>(g: &'a Grammar, dfa: &'a Dfa, namespace: &'a str) -> impl Display + 'a {
+// code in toml is usually written without indentation, or indented as a whole, which matters for python and gofmt
+// remove the common indentation of `code`, and indent each line by `ind` instead
+// an empty `code` becomes `empty`, because python doesn't allow empty blocks
+pub fn indent<'a>(code: &'a str, ind: &'a str, empty: &'a str) -> impl Display + 'a {
+  fmt_::fn2display(move |f| {
+    let lines = code.lines().filter(|l| !l.trim().is_empty());
+    let common = lines.clone().map(|l| l.len() - l.trim_start().len()).min().unwrap_or(0);
+    let mut first = true;
+    for l in lines {
+      if !first { f.write_char('\n')?; }
+      first = false;
+      write!(f, "{}{}", ind, l[common..].trim_end())?;
+    }
+    if first && !empty.is_empty() { write!(f, "{}{}", ind, empty)?; }
+    Ok(())
+  })
+}
+
+// advance the column after eating a byte `ch` which is not '\n'
+pub fn col_step<'a>(g: &'a Grammar, lang: Lang) -> impl Display + 'a {
+  fmt_::fn2display(move |f| {
+    let (rs, col) = if lang == Lang::Rs { (true, "self.col") } else { (false, "col") };
+    if let Some(w) = g.raw.tab_width {
+      let cond = match lang {
+        Lang::Rs => "if ch == b'\\t' as usize",
+        Lang::TypeScript => "if (ch === 9)", // `ch` is a number
+        _ => "if (ch == '\\t')",
+      };
+      write!(f, "{0} {{ {1} += {2} - ({1} - 1) % {2}; }} else ", cond, col, w)?;
+    }
+    // utf-8 continuation bytes are not counted
+    match (g.raw.unicode, rs) {
+      (true, true) => write!(f, "if ch & 0xC0 != 0x80 {{ {} += 1; }}", col),
+      (true, false) => write!(f, "if ((ch & 0xC0) != 0x80) {{ ++{}; }}", col),
+      (false, true) => write!(f, "{{ {} += 1; }}", col),
+      (false, false) => write!(f, "{{ ++{}; }}", col),
+    }
+  })
+}
+
+// the lexer keeps a stack of modes, the current mode is the top of it, or the initial mode (0) if it is empty
+// these are only generated when there are multiple modes, so that single mode lexers are not affected
+pub fn lexer_field<'a>(g: &'a Grammar, lang: Lang) -> impl Display + 'a {
+  fmt_::fn2display(move |f| {
+    if g.lexer_mode.len() > 1 {
+      f.write_str(match lang {
+        Lang::Rs => "pub mode: Vec<u32>,",
+        Lang::Cpp => "std::vector<u32> mode;",
+        Lang::Java => "public final ArrayList<Integer> mode = new ArrayList<>();",
+        Lang::C | Lang::Python | Lang::TypeScript | Lang::Go | Lang::Json | Lang::Template => unreachable!(),
+      })?;
+    }
+    if g.raw.indent.is_some() {
+      f.write_str(match lang {
+        Lang::Rs => "pub indents: Vec<u32>, pub pending: Vec<Token<'l>>, pub depth: u32, pub last_line: u32,",
+        Lang::Cpp => "std::vector<u32> indents; std::vector<Token> pending; u32 depth = 0, last_line = 0;",
+        Lang::Java => "private final ArrayList<Integer> indents = new ArrayList<>(); private final ArrayList<Token> pending = new ArrayList<>(); private int depth = 0, last_line = 0;",
+        Lang::C | Lang::Python | Lang::TypeScript | Lang::Go | Lang::Json | Lang::Template => unreachable!(),
+      })?;
+    }
+    // the byte offset of the remaining input, for computing `Span`
+    if g.raw.locations && lang == Lang::Rs { f.write_str("pub pos: usize,")?; }
+    f.write_str(g.raw.lexer_field)
+  })
+}
+
+// only for java, reset the fields added by `lexer_field`
+pub fn lexer_reset<'a>(g: &'a Grammar) -> impl Display + 'a {
+  fmt_::fn2display(move |f| {
+    if g.lexer_mode.len() > 1 { f.write_str("mode.clear();")?; }
+    if g.raw.indent.is_some() { f.write_str("indents.clear(); pending.clear(); depth = last_line = 0;")?; }
+    Ok(())
+  })
+}
+
+// when `indent` is enabled, the dfa lexer is renamed to `next_raw`, and `next` (generated by `layout`) emits layout tokens around its tokens
+pub fn next_name(g: &Grammar) -> &'static str {
+  if g.raw.indent.is_some() { "next_raw" } else { "next" }
+}
+
+// the indentation of a line is the column of its first token (minus 1), so `tab_width` and `unicode` also apply to it
+// blank lines and comment lines are skipped by `next_raw` (as `_Eps`), so they don't affect indentation
+// layout tokens have empty piece, and have the same location as the token after them
+pub fn layout<'a>(g: &'a Grammar, lang: Lang) -> impl Display + 'a {
+  fmt_::fn2display(move |f| {
+    let indent = match &g.raw.indent { Some(x) => x, None => return Ok(()) };
+    let id = |name: &str| g.terms.iter().position(|t| t.name == name).unwrap();
+    match lang {
+      Lang::Rs => {
+        write!(f, "pub fn next(&mut self)->Token<'l>{{if let Some(t)=self.pending.pop(){{return t;}}\
+          let t=self.next_raw();let eof=t.kind==TokenKind::_Eof;\
+          let last_line=std::mem::replace(&mut self.last_line,if eof{{0}}else{{self.line}});\
+          let line_start=self.depth==0&&(t.line>last_line||eof);")?;
+        if !indent.open.is_empty() || !indent.close.is_empty() {
+          f.write_str("match t.kind{")?;
+          if !indent.open.is_empty() { write!(f, "{}=>self.depth+=1,", fmt_::sep(indent.open.iter().map(|x| format!("TokenKind::{}", x)), "|"))?; }
+          if !indent.close.is_empty() { write!(f, "{}=>self.depth=self.depth.saturating_sub(1),", fmt_::sep(indent.close.iter().map(|x| format!("TokenKind::{}", x)), "|"))?; }
+          f.write_str("_=>{}}")?;
+        }
+        // `pending` is a stack, so tokens are pushed in reverse order
+        write!(f, "if !line_start{{return t;}}self.pending.push(t);\
+          let (width,piece)=(if eof{{0}}else{{t.col-1}},&t.piece[..0]);\
+          let mk=|kind|Token{{kind,piece,line:t.line,col:t.col}};\
+          let top=*self.indents.last().unwrap_or(&0);\
+          if width>top{{self.indents.push(width);self.pending.push(mk(TokenKind::{0}));}}\
+          else if width<top{{let mut dedent=0;\
+          while width<*self.indents.last().unwrap_or(&0){{self.indents.pop();dedent+=1;}}\
+          if width!=*self.indents.last().unwrap_or(&0){{self.pending.push(mk(TokenKind::_Err));}}\
+          for _ in 0..dedent{{self.pending.push(mk(TokenKind::{1}));}}}}\
+          if last_line!=0{{self.pending.push(mk(TokenKind::{2}));}}\
+          match self.pending.pop(){{Some(t)=>t,None=>err!()}}}}", indent.indent, indent.dedent, indent.newline)
+      }
+      Lang::Cpp => {
+        f.write_str("Token Lexer::next(){if(!pending.empty()){Token t=pending.back();pending.pop_back();return t;}\
+          Token t=next_raw();bool eof=t.kind==Token::_Eof;u32 last=last_line;last_line=eof?0:line;\
+          bool line_start=depth==0&&(t.line>last||eof);switch(t.kind){")?;
+        for x in &indent.open { write!(f, "case Token::{}:", x)?; }
+        if !indent.open.is_empty() { f.write_str("++depth;break;")?; }
+        for x in &indent.close { write!(f, "case Token::{}:", x)?; }
+        if !indent.close.is_empty() { f.write_str("if(depth){--depth;}break;")?; }
+        write!(f, "default:break;}}if(!line_start){{return t;}}pending.push_back(t);\
+          auto mk=[&](Token::Kind kind){{return Token{{kind,std::string_view(t.piece.data(),0),t.line,t.col}};}};\
+          u32 width=eof?0:t.col-1,top=indents.empty()?0:indents.back();\
+          if(width>top){{indents.push_back(width);pending.push_back(mk(Token::{0}));}}\
+          else if(width<top){{u32 dedent=0;\
+          while(!indents.empty()&&width<indents.back()){{indents.pop_back();++dedent;}}\
+          if(width!=(indents.empty()?0:indents.back())){{pending.push_back(mk(Token::_Err));}}\
+          while(dedent--){{pending.push_back(mk(Token::{1}));}}}}\
+          if(last!=0){{pending.push_back(mk(Token::{2}));}}\
+          t=pending.back();pending.pop_back();return t;}}", indent.indent, indent.dedent, indent.newline)
+      }
+      Lang::Java => {
+        f.write_str("Token next(){if(!pending.isEmpty()){return pending.remove(pending.size()-1);}\
+          Token t=next_raw();boolean eof=t.kind==EOF;int last=last_line;last_line=eof?0:line;\
+          boolean line_start=depth==0&&(t.line>last||eof);switch(t.kind){")?;
+        for x in &indent.open { write!(f, "case {}:", id(x))?; }
+        if !indent.open.is_empty() { f.write_str("++depth;break;")?; }
+        for x in &indent.close { write!(f, "case {}:", id(x))?; }
+        if !indent.close.is_empty() { f.write_str("if(depth>0){--depth;}break;")?; }
+        write!(f, "default:break;}}if(!line_start){{return t;}}pending.add(t);\
+          int width=eof?0:t.col-1,top=indents.isEmpty()?0:indents.get(indents.size()-1);\
+          if(width>top){{indents.add(width);pending.add(new Token({0},\"\",t.line,t.col));}}\
+          else if(width<top){{int dedent=0;\
+          while(!indents.isEmpty()&&width<indents.get(indents.size()-1)){{indents.remove(indents.size()-1);++dedent;}}\
+          if(width!=(indents.isEmpty()?0:indents.get(indents.size()-1))){{pending.add(new Token(ERR,\"\",t.line,t.col));}}\
+          while(dedent-->0){{pending.add(new Token({1},\"\",t.line,t.col));}}}}\
+          if(last!=0){{pending.add(new Token({2},\"\",t.line,t.col));}}\
+          return pending.remove(pending.size()-1);}}", id(indent.indent), id(indent.dedent), id(indent.newline))
+      }
+      Lang::C | Lang::Python | Lang::TypeScript | Lang::Go | Lang::Json | Lang::Template => unreachable!(),
+    }
+  })
+}
+
+// the start state of the current mode, the start state of mode i is state i (see `merge_dfa`)
+pub fn mode_start(g: &Grammar, lang: Lang) -> &'static str {
+  if g.lexer_mode.len() == 1 { return "0"; }
+  match lang {
+    Lang::Rs => "*self.mode.last().unwrap_or(&0) as usize",
+    Lang::Cpp => "(mode.empty() ? 0 : mode.back())",
+    Lang::Java => "(mode.isEmpty() ? 0 : mode.get(mode.size() - 1))",
+    Lang::C | Lang::Python | Lang::TypeScript | Lang::Go | Lang::Json | Lang::Template => unreachable!(),
+  }
+}
+
+// return (declaration of `last_state`, statement to update it when a state accepts)
+// `last_state` is the last accepting state, it decides which rule is matched, so that the mode transition can be performed
+pub fn mode_last_state(g: &Grammar, lang: Lang) -> (&'static str, &'static str) {
+  if g.lexer_mode.len() == 1 { return ("", ""); }
+  match lang {
+    Lang::Rs => ("let mut last_state = 0;", "last_state = nxt;"),
+    Lang::Cpp => ("u32 last_state = 0;", "last_state = nxt;"),
+    Lang::Java => ("int last_state = 0;", "last_state = nxt;"),
+    Lang::C | Lang::Python | Lang::TypeScript | Lang::Go | Lang::Json | Lang::Template => unreachable!(),
+  }
+}
+
+// perform the mode transition of the matched rule, it is executed for all tokens, including the skipped `_Eps`
+pub fn mode_trans<'a>(g: &'a Grammar, dfa: &'a Dfa, lang: Lang) -> impl Display + 'a {
+  fmt_::fn2display(move |f| {
+    if g.lexer_mode.len() == 1 { return Ok(()); }
+    let mut trans = Vec::<(ModeTrans, Vec<_>)>::new();
+    for (state, &(a, _)) in dfa.nodes.iter().enumerate() {
+      if let Some(a) = a {
+        let t = g.lexical[a as usize].trans;
+        if t == ModeTrans::Stay { continue; }
+        match trans.iter_mut().find(|x| x.0 == t) {
+          Some(x) => x.1.push(state),
+          None => trans.push((t, vec![state])),
+        }
+      }
+    }
+    let (cond, pop, push) = match lang {
+      Lang::Rs => ("if last_acc != TokenKind::_Err { match last_state {", "self.mode.pop();", "self.mode.push"),
+      Lang::Cpp => ("if (last_acc != Token::_Err) { switch (last_state) {", "if (!mode.empty()) { mode.pop_back(); }", "mode.push_back"),
+      Lang::Java => ("if (last_acc != ERR) { switch (last_state) {", "if (!mode.isEmpty()) { mode.remove(mode.size() - 1); }", "mode.add"),
+      Lang::C | Lang::Python | Lang::TypeScript | Lang::Go | Lang::Json | Lang::Template => unreachable!(),
+    };
+    f.write_str(cond)?;
+    for (t, states) in trans {
+      if lang == Lang::Rs { write!(f, "{}=>{{", fmt_::sep(states.iter(), "|"))?; } else {
+        for s in states { write!(f, "case {}:", s)?; }
+        f.write_str("{")?;
+      }
+      match t {
+        ModeTrans::Push(m) => write!(f, "{}({});", push, m)?,
+        ModeTrans::Switch(m) => write!(f, "{}{}({});", pop, push, m)?,
+        ModeTrans::Pop => f.write_str(pop)?,
+        ModeTrans::Stay => {}
+      }
+      f.write_str(if lang == Lang::Rs { "}" } else { "break;}" })?;
+    }
+    f.write_str(if lang == Lang::Rs { "_=>{}}}" } else { "}}" })
+  })
+}
+
+// `prefix` is prepended to the term names, e.g., "TokenKind::"
+pub fn acc<'a>(g: &'a Grammar, dfa: &'a Dfa, prefix: &'a str) -> impl Display + 'a {
   fmt_::fn2display(move |f| (for &(acc, _) in &dfa.nodes {
     match acc {
-      Some(acc) => { let _ = write!(f, "{}::{}, ", namespace, g.raw.lexical.get_index(acc as usize).unwrap().1); }
-      None => { let _ = write!(f, "{}::_Err, ", namespace); }
+      Some(acc) => { let _ = write!(f, "{}{}, ", prefix, g.lexical[acc as usize].term); }
+      None => { let _ = write!(f, "{}_Err, ", prefix); }
     }
   }, Ok(())).1)
 }
 
-pub fn dfa_edge<'a>(dfa: &'a Dfa, bracket: (char, char)) -> impl Display + 'a {
-  fmt_::fn2display(move |f| {
-    for (_, edges) in dfa.nodes.iter() {
-      let mut outs = [0; 256];
-      for (&k, &out) in edges {
-        assert_ne!(out, 0);
-        outs[k as usize] = out; }
-      write!(f, "{}{}{},", bracket.0, comma_sep(outs.iter().take(dfa.ec_num)), bracket.1)?;
+// `edge[state][ec]` is the next state, 0 is the dead state
+pub fn dfa_edge_rows(dfa: &Dfa) -> Vec<Vec<u32>> {
+  dfa.nodes.iter().map(|(_, edges)| {
+    let mut outs = vec![0; dfa.ec_num];
+    for (&k, &out) in edges {
+      assert_ne!(out, 0);
+      outs[k as usize] = out;
     }
+    outs
+  }).collect()
+}
+
+// each row in `bracket`, followed by `,`
+fn rows<'a>(rows: Vec<Vec<u32>>, bracket: (char, char)) -> impl Display + 'a {
+  fmt_::fn2display(move |f| {
+    for row in &rows { write!(f, "{}{}{},", bracket.0, comma_sep(row.iter()), bracket.1)?; }
     Ok(())
   })
 }
 
-pub fn goto<'a>(g: &'a Grammar, table: &'a Table, bracket: (char, char)) -> impl Display + 'a {
+pub fn dfa_edge<'a>(dfa: &'a Dfa, bracket: (char, char)) -> impl Display + 'a { rows(dfa_edge_rows(dfa), bracket) }
+
+// the transitions of a dfa state, grouped by target: Vec<(target, Vec<(lo, hi)>)>, bytes in [lo, hi] go to target
+fn dfa_ranges(dfa: &Dfa, state: usize) -> Vec<(usize, Vec<(usize, usize)>)> {
+  let mut outs = [0; 256];
+  for (&k, &out) in &dfa.nodes[state].1 { outs[k as usize] = out as usize; }
+  let out = |ch: usize| outs[dfa.ec[ch] as usize];
+  let mut ret = Vec::<(usize, Vec<_>)>::new();
+  let mut lo = 0;
+  while lo < 256 {
+    let mut hi = lo;
+    while hi + 1 < 256 && out(hi + 1) == out(lo) { hi += 1; }
+    if out(lo) != 0 {
+      match ret.iter_mut().find(|x| x.0 == out(lo)) {
+        Some(x) => x.1.push((lo, hi)),
+        None => ret.push((out(lo), vec![(lo, hi)])),
+      }
+    }
+    lo = hi + 1;
+  }
+  ret
+}
+
+// encode the dfa transitions as code instead of EC & DFA_EDGE, `state` is the current state, `ch` is the input byte
+// for rust it is an expression evaluated to the next state, for c++ it is a statement assigning the next state to `nxt`
+pub fn dfa_next_code<'a>(dfa: &'a Dfa, lang: Lang) -> impl Display + 'a {
+  fmt_::fn2display(move |f| {
+    if lang == Lang::Rs { f.write_str("match state{")?; } else { f.write_str("switch(state){")?; }
+    for state in 0..dfa.nodes.len() {
+      let ranges = dfa_ranges(dfa, state);
+      if lang == Lang::Rs {
+        write!(f, "{}=>match ch{{", state)?;
+        for (out, ranges) in ranges {
+          let ranges = fmt_::sep(ranges.into_iter().map(|(lo, hi)| fmt_::fn2display(move |f|
+            if lo == hi { write!(f, "{}", lo) } else { write!(f, "{}..={}", lo, hi) })), "|");
+          write!(f, "{}=>{},", ranges, out)?;
+        }
+        writeln!(f, "_=>0,}},")?;
+      } else {
+        write!(f, "case {}:nxt=", state)?;
+        for (out, ranges) in ranges {
+          let ranges = fmt_::sep(ranges.into_iter().map(|(lo, hi)| fmt_::fn2display(move |f|
+            if lo == hi { write!(f, "ch=={}", lo) } else { write!(f, "(ch>={}&&ch<={})", lo, hi) })), "||");
+          write!(f, "{}?{}:", ranges, out)?;
+        }
+        writeln!(f, "0;break;")?;
+      }
+    }
+    // the state is always valid, but the c++ code should be portable, so it goes to the dead state, i.e., rejects the char
+    if lang == Lang::Rs { f.write_str("_=>err!(),}") } else { f.write_str("default:nxt=0;break;}") }
+  })
+}
+
+// for rust it is an expression evaluated to the accepted token kind of state `nxt`, for c++ it is a statement assigning it to `acc`
+pub fn dfa_acc_code<'a>(g: &'a Grammar, dfa: &'a Dfa, lang: Lang) -> impl Display + 'a {
+  fmt_::fn2display(move |f| {
+    let mut acc = Vec::<(&str, Vec<_>)>::new();
+    for (state, &(a, _)) in dfa.nodes.iter().enumerate() {
+      if let Some(a) = a {
+        let term = g.lexical[a as usize].term;
+        match acc.iter_mut().find(|x| x.0 == term) {
+          Some(x) => x.1.push(state),
+          None => acc.push((term, vec![state])),
+        }
+      }
+    }
+    if lang == Lang::Rs {
+      f.write_str("match nxt{")?;
+      for (term, states) in acc { write!(f, "{}=>TokenKind::{},", fmt_::sep(states.iter(), "|"), term)?; }
+      f.write_str("_=>TokenKind::_Err,}")
+    } else {
+      f.write_str("switch(nxt){")?;
+      for (term, states) in acc {
+        for s in states { write!(f, "case {}:", s)?; }
+        write!(f, "acc=Token::{};break;", term)?;
+      }
+      f.write_str("default:acc=Token::_Err;}")
+    }
+  })
+}
+
+// the code to run when ACTION has no entry for the token, i.e., `act & 3 == 3`
+// the token kind is changed to the first fallback term that has an action in the current state, and the loop continues
+// otherwise the token is returned as error
+pub fn lr_error<'a>(g: &'a Grammar, lang: Lang) -> impl Display + 'a {
+  fmt_::fn2display(move |f| {
+    let name = |t: u32| g.terms[t as usize].name;
+    match lang {
+      Lang::Rs => {
+        if g.fallback.is_empty() { return f.write_str("return Err(token.into())"); }
+        f.write_str("{let fallback:&[TokenKind]=match token.kind{")?;
+        for (from, to) in &g.fallback {
+          write!(f, "TokenKind::{}=>&[{}],", name(*from), comma_sep(to.iter().map(|&x| format!("TokenKind::{}", name(x)))))?;
+        }
+        f.write_str("_=>&[],};match fallback.iter().find(|&&k|*idx!(idx!(ACTION,state),k as usize)&3!=3){\
+          Some(&k)=>token.kind=k,None=>return Err(token.into()),}}")
+      }
+      Lang::Cpp | Lang::Java => {
+        if !g.fallback.is_empty() {
+          f.write_str("switch(token.kind){")?;
+          for (from, to) in &g.fallback {
+            if lang == Lang::Cpp { write!(f, "case Token::{}:", name(*from))?; } else { write!(f, "case {}:", from)?; }
+            for &x in to {
+              if lang == Lang::Cpp {
+                write!(f, "if((ACTION[state][Token::{0}]&3)!=3){{token.kind=Token::{0};continue;}}", name(x))?;
+              } else {
+                // Token.kind is final
+                write!(f, "if((ACTION[state][{0}]&3)!=3){{token=new Token({0},token.piece,token.line,token.col);continue;}}", x)?;
+              }
+            }
+            f.write_str("break;")?;
+          }
+          f.write_str("default:break;}")?;
+        }
+        f.write_str(if lang == Lang::Cpp { "return token;" } else { "Result r=new Result();r.err=token;return r;" })
+      }
+      Lang::C => unreachable!(), // see `Config::c_error`, it needs the prefix of names
+      Lang::Python => unreachable!(), // the fallback terms are stored in a `FALLBACK` dict
+      Lang::TypeScript => unreachable!(), // see `Config::ts_error`, ACTION is flattened
+      Lang::Go => unreachable!(), // see `Config::go_error`, it needs to be gofmt-clean
+      Lang::Json | Lang::Template => unreachable!(), // see `model::Model::fallback`
+    }
+  })
+}
+
+// `goto[state][nt]` is the state to goto after reducing to `nt`, or 0 if there is no such edge
+pub fn goto_rows(g: &Grammar, table: &Table) -> Vec<Vec<u32>> {
+  // iterate over all non-terminals
+  table.iter().map(|t| (g.terms.len()..g.token_num()).map(|x| *t.goto.get(&(x as u32)).unwrap_or(&0)).collect()).collect()
+}
+
+pub fn goto<'a>(g: &'a Grammar, table: &'a Table, bracket: (char, char)) -> impl Display + 'a { rows(goto_rows(g, table), bracket) }
+
+// `action[state][term]` is `tag | (val << 2)`, tag 0: shift, 1: reduce, 2: accept, 3: error (see `model::Model::action`)
+pub fn action_rows(g: &Grammar, table: &Table) -> Vec<Vec<u32>> {
+  table.iter().map(|TableEntry { act, .. }| (0..g.terms.len() as u32).map(|i| {
+    let (tag, val) = act.get(&i).and_then(|x| x.get(0))
+      .map(|&x| match x { Act::Acc => (2, 0), Act::Shift(x) => (0, x), Act::Reduce(x) => (1, x) })
+      .unwrap_or((3, 0));
+    tag | (val << 2)
+  }).collect()).collect()
+}
+
+pub fn action<'a>(g: &'a Grammar, table: &'a Table, bracket: (char, char)) -> impl Display + 'a { rows(action_rows(g, table), bracket) }
+
+// FOLLOW[nt][term] is 1 if term is in the follow set of nt, otherwise 0
+pub fn ll_follow<'a>(g: &'a Grammar, ll: &'a LLCtx, bracket: (char, char)) -> impl Display + 'a {
   fmt_::fn2display(move |f| {
-    for t in table {
-      // iterate over all non-terminals
-      let goto = comma_sep((g.terms.len()..g.token_num()).map(|x| t.goto.get(&(x as u32)).unwrap_or(&0)));
-      write!(f, "{}{}{},", bracket.0, goto, bracket.1)?;
+    for i in 0..g.nt.len() {
+      let follow = ll.follow.get(i);
+      write!(f, "{}{}{},", bracket.0, comma_sep((0..g.terms.len()).map(move |t| bitset::ibs(follow).get(t) as u32)), bracket.1)?;
     }
     Ok(())
   })
 }
 
-pub fn action<'a>(g: &'a Grammar, table: &'a Table, bracket: (char, char)) -> impl Display + 'a {
+// TABLE[nt][term] is 0 if there is no production to expand, otherwise it is (prod id) + 1
+// if there are conflicts, the first production is selected (which is consistent with `show_ll::table`)
+pub fn ll_table<'a>(g: &'a Grammar, ll: &'a LLCtx, bracket: (char, char)) -> impl Display + 'a {
   fmt_::fn2display(move |f| {
-    for TableEntry { act, .. } in table {
-      f.write_char(bracket.0)?;
-      for i in 0..g.terms.len() as u32 {
-        let (tag, val) = act.get(&i).and_then(|x| x.get(0))
-          .map(|&x| match x { Act::Acc => (2, 0), Act::Shift(x) => (0, x), Act::Reduce(x) => (1, x) })
-          .unwrap_or((3, 0));
-        write!(f, "{},", tag | (val << 2))?;
-      };
-      write!(f, "{},", bracket.1)?;
+    for t in &ll.table {
+      let row = (0..g.terms.len() as u32).map(move |ch| t.get(&ch).map(|prods| prods[0] + 1).unwrap_or(0));
+      write!(f, "{}{}{},", bracket.0, comma_sep(row), bracket.1)?;
     }
     Ok(())
   })
-}
\ No newline at end of file
+}
+
+// return (RHS, RHS_START), the rhs of production i is RHS[RHS_START[i]..RHS_START[i + 1]]
+pub fn ll_rhs<'a>(g: &'a Grammar) -> (impl Display + 'a, impl Display + 'a) {
+  let rhs = comma_sep(g.prod.iter().flat_map(|x| x.rhs.iter()));
+  let rhs_start = comma_sep(std::iter::once(0).chain(g.prod.iter().scan(0, |acc, x| {
+    *acc += x.rhs.len();
+    Some(*acc)
+  })));
+  (rhs, rhs_start)
+}
@@ -0,0 +1,113 @@
+use crate::*;
+
+// Go has no `u8` like types, map `fmt::min_u` to the builtin ones
+fn min_u(x: usize) -> String { format!("uint{}", &fmt::min_u(x)[1..]) }
+
+// gofmt puts each row of a 2-d table on its own line
+fn rows(table: Vec<Vec<u32>>) -> String {
+  let mut s = String::new();
+  for row in table { let _ = writeln!(s, "\t{{{}}},", fmt_::sep(row.iter(), ", ")); }
+  s
+}
+
+impl<W: std::io::Write> Config<'_, W> {
+  // the code to run when ACTION has no entry for the token, like `fmt::lr_error`, but formatted as gofmt does
+  // the template returns the `SyntaxError` after it
+  fn go_error<'a>(g: &'a Grammar) -> impl Display + 'a {
+    fmt_::fn2display(move |f| {
+      let name = |t: u32| g.terms[t as usize].name;
+      if g.fallback.is_empty() { return Ok(()); }
+      f.write_str("\t\t\tswitch token.Kind {\n")?;
+      for (from, to) in &g.fallback {
+        writeln!(f, "\t\t\tcase {}:", name(*from))?;
+        for &x in to {
+          writeln!(f, "\t\t\t\tif action[state][{0}]&3 != 3 {{\n\t\t\t\t\ttoken.Kind = {0}\n\t\t\t\t\tcontinue\n\t\t\t\t}}", name(x))?;
+        }
+      }
+      f.write_str("\t\t\t}\n")
+    })
+  }
+
+  // values are stored as `interface{}`, and type asserted to the `ty` of non-terminals or `Token`
+  fn go_act<'a>(g: &'a Grammar) -> impl Display + 'a {
+    fmt_::fn2display(move |f| {
+      for (i, prod) in g.prod.iter().enumerate() {
+        writeln!(f, "\t\t\tcase {}:", i)?;
+        let name = |j: usize| match prod.args { Some(args) => args[j].0.to_owned(), None => format!("_{}", j + 1) };
+        let len = prod.rhs.len();
+        for (j, &x) in prod.rhs.iter().enumerate().rev() {
+          let ty = if let Some(x) = g.as_nt(x) { g.nt[x].ty } else { "Token" };
+          writeln!(f, "\t\t\t\t{} := stk[len(stk)-{}].value.({})", name(j), len - j, ty)?;
+        }
+        if len != 0 {
+          // go doesn't allow unused variables
+          writeln!(f, "\t\t\t\t{} = {}", fmt_::sep((0..len).map(|_| "_"), ", "), fmt_::sep((0..len).map(name), ", "))?;
+          writeln!(f, "\t\t\t\tstk = stk[:len(stk)-{}]", len)?;
+        }
+        writeln!(f, "\t\t\t\tvar __ {}", g.nt[prod.lhs as usize].ty)?;
+        if i == g.prod.len() - 1 {
+          f.write_str("\t\t\t\t__ = _1\n")?;
+        } else {
+          let act = fmt::act(g, prod, Lang::Go);
+          if !act.trim().is_empty() { writeln!(f, "{}", fmt::indent(&act, "\t\t\t\t", ""))?; }
+        }
+        f.write_str("\t\t\t\tvalue = __\n")?;
+      }
+      Ok(())
+    })
+  }
+
+  pub fn go_lalr1(&mut self, g: &Grammar, table: &Table, dfa: &Dfa) -> Result<()> {
+    // optional parts carry their own blank lines, since gofmt allows at most one blank line in a row
+    let block = |code: Option<&str>| code.map(str::trim).filter(|x| !x.is_empty()).map(|x| format!("\n{}\n", x)).unwrap_or_default();
+    write!(
+      self.code_output, include_str!("template/lalr1.go.template"),
+      package = self.package,
+      include = block(Some(g.raw.include)),
+      token_kind = fmt_::fn2display(|f| {
+        for (i, t) in g.terms.iter().enumerate() {
+          writeln!(f, "\t{}{}", t.name, if i == 0 { " TokenKind = iota" } else { "" })?;
+        }
+        Ok(())
+      }),
+      acc = fmt::acc(g, dfa, "").to_string().trim_end_matches(", "),
+      ec = fmt_::sep(dfa.ec.iter(), ", "),
+      ec_num = dfa.ec_num,
+      u_dfa_size = min_u(dfa.nodes.len()),
+      dfa_edge = rows(fmt::dfa_edge_rows(dfa)),
+      lexer_field = fmt_::fn2display(move |f| if g.raw.lexer_field.trim().is_empty() { Ok(()) } else {
+        write!(f, "\n\n{}", fmt::indent(g.raw.lexer_field, "\t", ""))
+      }),
+      lexer_action = fmt_::fn2display(move |f| if g.raw.lexer_action.trim().is_empty() { Ok(()) } else {
+        writeln!(f, "{}", fmt::indent(g.raw.lexer_action, "\t\t\t\t", ""))
+      }),
+      col_step = fmt_::fn2display(move |f| {
+        // utf-8 continuation bytes are not counted, like `fmt::col_step`
+        let step = if g.raw.unicode { "if ch&0xC0 != 0x80 {\n\t\t\t\t\tcol++\n\t\t\t\t}" } else { "col++" };
+        match g.raw.tab_width {
+          Some(w) if g.raw.unicode => write!(f, "\t\t\t\tif ch == '\\t' {{\n\t\t\t\t\tcol += {0} - (col-1)%{0}\n\t\t\t\t}} else {1}", w, step),
+          Some(w) => write!(f, "\t\t\t\tif ch == '\\t' {{\n\t\t\t\t\tcol += {0} - (col-1)%{0}\n\t\t\t\t}} else {{\n\t\t\t\t\tcol++\n\t\t\t\t}}", w),
+          None => write!(f, "\t\t\t\t{}", step),
+        }
+      }),
+      parser_struct = fmt_::fn2display(move |f| match (g.raw.parser_def, g.raw.parser_field.trim()) {
+        (Some(_), _) => Ok(()),
+        (None, "") => f.write_str("\n// Parser parses the Tokens from a Lexer\ntype Parser struct{}\n"),
+        (None, field) => write!(f, "\n// Parser parses the Tokens from a Lexer\ntype Parser struct {{\n{}\n}}\n", fmt::indent(field, "\t", "")),
+      }),
+      u_nt_size = min_u(g.nt.len()),
+      prod = fmt_::sep(g.prod.iter().map(|x| x.lhs), ", "),
+      term_num = g.terms.len(),
+      u_act_size = min_u(table.len() * 4),
+      action = rows(fmt::action_rows(g, table)),
+      nt_num = g.nt.len(),
+      u_lr_fsm_size = min_u(table.len()),
+      goto = rows(fmt::goto_rows(g, table)),
+      parser_type = g.raw.parser_def.unwrap_or("Parser"),
+      res_type = g.nt.last().unwrap().ty,
+      parser_act = Self::go_act(g),
+      on_error = Self::go_error(g),
+      epilogue = block(g.raw.epilogue),
+    )
+  }
+}
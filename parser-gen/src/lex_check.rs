@@ -0,0 +1,89 @@
+// check the lexical rules of one lexer mode, find rules that never match, are partially shadowed by earlier rules, or match the empty string
+// the earlier rule wins when two rules match the same string, so a rule is checked by walking its own dfa together with the dfa of all rules
+use crate::*;
+use std::collections::VecDeque;
+
+// `ret[state][ch]` is the next state after eating byte `ch`, 0 means dead
+fn dense(dfa: &Dfa) -> Vec<[u32; 256]> {
+  dfa.nodes.iter().map(|(_, edges)| {
+    let mut outs = [0; 256];
+    for (&k, &out) in edges { outs[k as usize] = out; }
+    let mut ret = [0; 256];
+    for ch in 0..256 { ret[ch] = outs[dfa.ec[ch] as usize]; }
+    ret
+  }).collect()
+}
+
+// whether the dfa accepts finitely many strings, e.g., a keyword
+fn finite(dfa: &Dfa) -> bool {
+  // color: 0 = unvisited, 1 = on the dfs stack, 2 = finished
+  fn dfs(dfa: &Dfa, x: usize, color: &mut [u8]) -> bool {
+    color[x] = 1;
+    for &out in dfa.nodes[x].1.values() {
+      let out = out as usize;
+      if color[out] == 1 || (color[out] == 0 && !dfs(dfa, out, color)) { return false; }
+    }
+    color[x] = 2;
+    true
+  }
+  dfa.nodes.is_empty() || dfs(dfa, 0, &mut vec![0; dfa.nodes.len()])
+}
+
+fn show(s: &[u8]) -> String { format!("{:?}", String::from_utf8_lossy(s)) }
+
+// `dfa` is built from all `rules` of this mode (accepting state `Some(i)` means `rules[i]`), `rule_dfa[i]` is built from `rules[i]` alone
+// return the warning messages
+pub fn check(rules: &[LexRule], rule_dfa: &[Dfa], dfa: &Dfa) -> Vec<String> {
+  let name = |i: usize| format!("lexical rule \"{}\" ({})", rules[i].re, rules[i].term);
+  let finite = rule_dfa.iter().map(finite).collect::<Vec<_>>();
+  let all = dense(dfa);
+  let mut ret = Vec::new();
+  for (i, d) in rule_dfa.iter().enumerate() {
+    if d.nodes.is_empty() { continue; }
+    if d.nodes[0].0.is_some() { ret.push(format!("{} can match the empty string", name(i))); }
+    let one = dense(d);
+    // bfs on pairs of (state in `d`, state in `dfa`), so the first example found is the shortest one
+    // `pre` maps a pair to its predecessor and the byte on the edge, for reconstructing the example
+    let mut pre = HashMap::default();
+    let mut q = VecDeque::new();
+    pre.insert((0, 0), None);
+    q.push_back((0, 0));
+    let (mut win, mut lose) = (false, None);
+    while let Some((a, b)) = q.pop_front() {
+      if d.nodes[a as usize].0.is_some() {
+        // all strings accepted by `d` are also accepted by `dfa`
+        match dfa.nodes[b as usize].0 {
+          Some(w) if w as usize == i => win = true,
+          Some(w) => if lose.is_none() { lose = Some(((a, b), w as usize)); },
+          None => {}
+        }
+        if win && lose.is_some() { break; }
+      }
+      for ch in 0..256 {
+        let nxt = (one[a as usize][ch], all[b as usize][ch]);
+        if nxt.0 != 0 && nxt.1 != 0 && !pre.contains_key(&nxt) {
+          pre.insert(nxt, Some((a, b, ch as u8)));
+          q.push_back(nxt);
+        }
+      }
+    }
+    let example = |mut s| {
+      let mut v = Vec::new();
+      while let Some(&Some((a, b, ch))) = pre.get(&s) {
+        v.push(ch);
+        s = (a, b);
+      }
+      v.reverse();
+      show(&v)
+    };
+    match lose {
+      Some((s, w)) if !win => ret.push(format!("{} never matches, it is shadowed by earlier rules, e.g., {} on {}", name(i), name(w), example(s))),
+      Some((s, w)) if finite[i] && !finite[w] => ret.push(format!("{} is swallowed by {} on {}, consider moving it before the latter", name(i), name(w), example(s))),
+      // a general rule (e.g., identifier) shadowed by a keyword rule is intended
+      Some((s, w)) if rules[i].term != rules[w].term && !(finite[w] && !finite[i]) =>
+        ret.push(format!("{} is partially shadowed by {} on {}", name(i), name(w), example(s))),
+      _ => {}
+    }
+  }
+  ret
+}
@@ -1,7 +1,17 @@
 mod fmt;
+mod unicode;
+mod definitions;
+mod lex_check;
 pub mod rs;
 pub mod cpp;
 pub mod java;
+pub mod c;
+pub mod python;
+pub mod ts;
+pub mod go;
+pub mod model;
+#[cfg(feature = "toml")]
+pub mod build;
 pub mod show_lr;
 pub mod show_ll;
 
@@ -9,25 +19,45 @@ use common::*;
 use lalr1_core::*;
 use ll1_core::*;
 use re2dfa::*;
-use std::{fs::File, io::{Result, Write, BufWriter}, fmt::Write as _};
+use std::{fs::File, io::{Error, ErrorKind, Result, Write, BufWriter}, fmt::Write as _};
 
+// the errors are returned by `work`, implementations can override them to record where the error is
 pub trait Codegen {
-  fn grammar_error(&mut self, reason: String) -> ! { panic!("invalid grammar, reason: {}", reason) }
+  fn grammar_error(&mut self, reason: String) -> Error { Error::new(ErrorKind::InvalidData, format!("invalid grammar, reason: {}", reason)) }
 
-  fn re2dfa_error(&mut self, re: &str, reason: String) -> ! { panic!("invalid regex {}, reason: {}", re, reason) }
+  fn re2dfa_error(&mut self, re: &str, reason: String) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("invalid regex {}, reason: {}", re, reason))
+  }
+
+  // problems found in lexical rules that don't prevent generating the lexer, e.g., a rule that never matches
+  fn lexical_warning(&mut self, _reason: String) {}
 
-  fn dfa(&mut self, dfa: &Dfa);
+  fn dfa(&mut self, dfa: &Dfa) -> Result<()>;
 
   fn ll(&mut self, g: &Grammar, ll: LLCtx, dfa: &Dfa) -> Result<()>;
 
   fn lr1(&mut self, g: &Grammar, lr1: &Lr1Fsm, dfa: &Dfa, orig_table: Table, table: Table, conflict: Vec<Conflict>) -> Result<()>;
+
+  // the language of the actions, which decides the literals skipped when finding placeholders
+  fn lang(&self) -> Lang;
 }
 
 #[derive(Eq, PartialEq, Copy, Clone)]
 pub enum PGAlgo { LL1, LR1, LALR1 }
 
 #[derive(Eq, PartialEq, Copy, Clone)]
-pub enum Lang { Rs, Cpp, Java }
+pub enum Lang { Rs, Cpp, Java, C, Python, TypeScript, Go, Json, Template }
+
+impl Lang {
+  // json & template are for other languages, whose literals are assumed to be like c
+  pub fn quotes(self) -> Quotes {
+    match self {
+      Lang::Rs => Quotes::Rust,
+      Lang::Python | Lang::TypeScript | Lang::Go => Quotes::Script,
+      Lang::Cpp | Lang::Java | Lang::C | Lang::Json | Lang::Template => Quotes::C,
+    }
+  }
+}
 
 pub struct Config<'a, W> {
   pub verbose: Option<&'a str>,
@@ -36,9 +66,53 @@ pub struct Config<'a, W> {
   pub log_token: bool,
   pub log_reduce: bool,
   pub use_unsafe: bool,
+  // only for LL(1) & Rust: generate a recursive descent parser, instead of the table-driven one
+  pub recursive_descent: bool,
+  // only for LR(1)/LALR(1) & Rust: encode the lr fsm as code and use typed value stacks, instead of using ACTION/GOTO table and `StackItem`
+  pub direct_lr: bool,
+  // only for Rust & C++: encode the lexer dfa as code, instead of using EC/DFA_EDGE table
+  pub direct_lexer: bool,
   pub lang: Lang,
   pub on_conflict: fn(String),
+  // called with the problems found in lexical rules, see `Codegen::lexical_warning`
+  pub on_lexical_warning: fn(String),
   pub code_output: W,
+  // only for C: (the name used in `#include`, output) of the header file
+  // if it is None, the content of the header is put at the beginning of `code_output`
+  pub header_output: Option<(&'a str, W)>,
+  // only for C: the prefix of all global names in the generated code, so that several parsers can be linked together
+  pub prefix: &'a str,
+  // only for Go: the name of the generated package
+  pub package: &'a str,
+  // only for `Lang::Template`: the path of the tera template, which is rendered with `model::Model`
+  pub template: &'a str,
+}
+
+impl<'a, W> Config<'a, W> {
+  // only `lang` and `code_output` are required, the others default to: no debug output or logging, table-driven parser & lexer,
+  // no header, no prefix, package "parser" and no template, conflicts and lexical warnings are ignored
+  // set other fields with the struct update syntax, e.g., `Config { use_unsafe: true, ..Config::new(Lang::Rs, output) }`
+  pub fn new(lang: Lang, code_output: W) -> Config<'a, W> {
+    Config {
+      verbose: None,
+      show_fsm: None,
+      show_dfa: None,
+      log_token: false,
+      log_reduce: false,
+      use_unsafe: false,
+      recursive_descent: false,
+      direct_lr: false,
+      direct_lexer: false,
+      lang,
+      on_conflict: |_| {},
+      on_lexical_warning: |_| {},
+      code_output,
+      header_output: None,
+      prefix: "",
+      package: "parser",
+      template: "",
+    }
+  }
 }
 
 fn write(path: &str, s: impl Display) -> Result<()> {
@@ -46,49 +120,175 @@ fn write(path: &str, s: impl Display) -> Result<()> {
 }
 
 impl<W: Write> Codegen for Config<'_, W> {
-  fn dfa(&mut self, dfa: &Dfa) {
+  fn lexical_warning(&mut self, reason: String) { (self.on_lexical_warning)(reason); }
+
+  fn lang(&self) -> Lang { self.lang }
+
+  fn dfa(&mut self, dfa: &Dfa) -> Result<()> {
     // these 2 characteristics make lexer behaviour hard to define and make lex generator hard to write
-    if dfa.nodes.is_empty() || dfa.nodes[0].0.is_some() { panic!("final dfa is not suitable for a lexer, i.e., it doesn't accept anything, or it accepts empty string"); }
-    if let Some(path) = self.show_dfa {
-      write(path, dfa.print_dot()).expect("failed to write dfa");
+    if dfa.nodes.is_empty() || dfa.nodes[0].0.is_some() {
+      return Err(self.grammar_error("final dfa is not suitable for a lexer, i.e., it doesn't accept anything, or it accepts empty string".to_owned()));
     }
+    if let Some(path) = self.show_dfa { write(path, dfa.print_dot())?; }
+    Ok(())
   }
 
   fn ll(&mut self, g: &Grammar, ll: LLCtx, dfa: &Dfa) -> Result<()> {
     if let Some(path) = self.verbose {
-      write(path, show_ll::table(&ll, g)).expect("failed to write ll1 table");
+      write(path, show_ll::table(&ll, g))?;
     }
     for c in show_ll::conflict(&ll.table, g) { (self.on_conflict)(c); }
+    self.check_feature(g, true)?;
     match self.lang {
       Lang::Rs => self.rs_ll1(&g, &ll, dfa),
-      _ => unimplemented!("ll1 codegen is currently only implemented for rust"),
+      Lang::Cpp => self.cpp_ll1(&g, &ll, dfa),
+      Lang::Java => self.java_ll1(&g, &ll, dfa),
+      Lang::C | Lang::Python | Lang::TypeScript | Lang::Go | Lang::Json | Lang::Template => unreachable!(), // checked in `check_feature`
     }
   }
 
   fn lr1(&mut self, g: &Grammar, lr1: &Lr1Fsm, dfa: &Dfa, orig_table: Table, table: Table, conflict: Vec<Conflict>) -> Result<()> {
     if let Some(path) = self.verbose {
-      write(path, show_lr::table(&orig_table, &table, g)).expect("failed to write lr1 table");
+      write(path, show_lr::table(&orig_table, &table, g))?;
     }
     if let Some(path) = self.show_fsm {
-      write(path, show_lr::lr1_dot(g, &lr1)).expect("failed to write lr1 fsm");
+      write(path, show_lr::lr1_dot(g, &lr1))?;
     }
     for c in show_lr::conflict(g, &conflict) { (self.on_conflict)(c); }
-    if conflict.iter().any(Conflict::is_many) { panic!(">= 3 conflicts on one token, give up solving conflicts"); }
+    if conflict.iter().any(Conflict::is_many) { return Err(self.grammar_error(">= 3 conflicts on one token, give up solving conflicts".to_owned())); }
+    self.check_feature(g, false)?;
     match self.lang {
       Lang::Rs => self.rs_lalr1(&g, &table, dfa),
       Lang::Cpp => self.cpp_lalr1(&g, &table, dfa),
       Lang::Java => self.java_lalr1(&g, &table, dfa),
+      Lang::C => self.c_lalr1(&g, &table, dfa),
+      Lang::Python => self.py_lalr1(&g, &table, dfa),
+      Lang::TypeScript => self.ts_lalr1(&g, &table, dfa),
+      Lang::Go => self.go_lalr1(&g, &table, dfa),
+      Lang::Json | Lang::Template => self.model_lalr1(&g, &table, dfa, &conflict),
+    }
+  }
+}
+
+impl<W: Write> Config<'_, W> {
+  // report the features used by `g` that are not supported by the language or the parsing algorithm
+  fn check_feature(&mut self, g: &Grammar, ll: bool) -> Result<()> {
+    let (rs, rd) = (self.lang == Lang::Rs, ll && self.recursive_descent);
+    if ll && !g.fallback.is_empty() { return Err(self.grammar_error("fallback terms are only supported by LR(1)/LALR(1) parsers".to_owned())); }
+    if g.terms.iter().any(|t| t.value.is_some()) && !(rs && (!ll || rd)) {
+      return Err(self.grammar_error("term values are only supported by Rust LR(1)/LALR(1) and recursive descent parsers".to_owned()));
     }
+    if g.raw.error_ty.is_some() && !rs { return Err(self.grammar_error("`error_ty` is only supported by Rust".to_owned())); }
+    if g.raw.locations && !(rs && (!ll || rd)) {
+      return Err(self.grammar_error("locations are only supported by Rust LR(1)/LALR(1) and recursive descent parsers".to_owned()));
+    }
+    // the layout tokens are emitted after the next token is lexed, so `lexer.pos` doesn't give their spans
+    if g.raw.locations && g.raw.indent.is_some() { return Err(self.grammar_error("locations are not supported with indentation".to_owned())); }
+    // these backends only have a table-driven LR parser, and a lexer without modes
+    let lang = match self.lang {
+      Lang::C => Some("C"), Lang::Python => Some("Python"), Lang::TypeScript => Some("TypeScript"), Lang::Go => Some("Go"),
+      Lang::Json => Some("JSON"), Lang::Template => Some("Template"), _ => None,
+    };
+    if let Some(lang) = lang {
+      if ll { return Err(self.grammar_error(format!("{} only supports LR(1)/LALR(1) parsers", lang))); }
+      if g.lexer_mode.len() > 1 || g.raw.indent.is_some() { return Err(self.grammar_error(format!("lexer modes and indentation are not supported by {}", lang))); }
+    }
+    Ok(())
   }
 }
 
+// merge the dfas of all lexer modes into one, `dfas[i].1` is the index of the first rule of mode i in `Grammar::lexical`
+// the start state of mode i is state i, and other states follow them
+// no edge points to a start state, so state 0 can still be used as the dead state
+fn merge_dfa(mut dfas: Vec<(Dfa, u32)>) -> Dfa {
+  if dfas.len() == 1 { return dfas.pop().unwrap().0; }
+  // refine the equivalence classes to be consistent with all dfas, `rep[i]` is a char in the new class i
+  let (mut ec, mut rep, mut ec2id) = ([0; 256], Vec::new(), HashMap::default());
+  for ch in 0..256 {
+    let key = dfas.iter().map(|(d, _)| d.ec[ch]).collect::<Vec<_>>();
+    ec[ch] = *ec2id.entry(key).or_insert_with(|| {
+      rep.push(ch);
+      rep.len() - 1
+    }) as u8;
+  }
+  let (mut start, mut rest) = (Vec::new(), Vec::new());
+  let mut base = dfas.len() as u32;
+  for (mode, (d, rule_offset)) in dfas.iter_mut().enumerate() {
+    let len = d.nodes.len() as u32;
+    let map = |state: u32| if state == 0 { mode as u32 } else { base + state - 1 };
+    for (state, (mut acc, mut edges)) in std::mem::take(&mut d.nodes).into_iter().enumerate() {
+      if let Some(acc) = &mut acc { *acc += *rule_offset; }
+      for (k, out) in std::mem::take(&mut edges) {
+        for (new_k, &ch) in rep.iter().enumerate() {
+          if d.ec[ch] as usize == k as usize { edges.insert(new_k as _, map(out)); }
+        }
+      }
+      if state == 0 { start.push((acc, edges)); } else { rest.push((acc, edges)); }
+    }
+    base += len - 1;
+  }
+  let mut dfa = dfas.swap_remove(0).0;
+  start.extend(rest);
+  dfa.nodes = start;
+  dfa.ec = ec;
+  dfa.ec_num = rep.len();
+  dfa
+}
+
 pub fn work(mut raw: RawGrammar, algo: PGAlgo, gen: &mut impl Codegen) -> Result<()> {
   use PGAlgo::*;
-  let dfa = match re2dfa(raw.lexical.iter().map(|(s, _)| s.as_bytes())) {
-    Ok(x) => x, Err((idx, reason)) => gen.re2dfa_error(raw.lexical.get_index(idx).unwrap().0, reason)
-  };
-  gen.dfa(&dfa);
-  let ref g = match raw.extend(true) { Ok(x) => x, Err(reason) => gen.grammar_error(reason) };
+  let ref g = match raw.extend(true) { Ok(x) => x, Err(reason) => return Err(gen.grammar_error(reason)) };
+  for p in &g.prod {
+    let rhs = p.rhs.iter().map(|&x| g.show_token(x as _)).collect::<Vec<_>>();
+    if let Err(reason) = placeholders(p.act, &rhs, g.raw.locations, gen.lang().quotes()) {
+      return Err(gen.grammar_error(format!("production \"{} -> {}\" action: {}", g.nt[p.lhs as usize].name, rhs.join(" "), reason)));
+    }
+  }
+  let defs = &g.raw.definitions;
+  // check each definition separately, so that errors in them are not reported on the lexical rules using them
+  for (&name, def) in defs {
+    let re = match definitions::expand(def, defs) {
+      Ok((x, _)) => x, Err(reason) => return Err(gen.grammar_error(format!("definition \"{}\": {}", name, reason)))
+    };
+    let re = match unicode::expand(&re, g.raw.unicode) {
+      Ok(x) => x, Err(reason) => return Err(gen.grammar_error(format!("definition \"{}\": {}", name, reason)))
+    };
+    if let Err((_, reason)) = re2dfa(std::iter::once(&*re)) {
+      return Err(gen.grammar_error(format!("definition \"{}\" is not a valid regex: {}", name, reason)));
+    }
+  }
+  let mut dfas = Vec::with_capacity(g.lexer_mode.len());
+  for (mode, &name) in g.lexer_mode.iter().enumerate() {
+    let offset = g.lexical.iter().position(|x| x.mode == mode as u32).unwrap_or(g.lexical.len());
+    let rules = &g.lexical[offset..offset + g.lexical[offset..].iter().take_while(|x| x.mode == mode as u32).count()];
+    let mut expanded = Vec::with_capacity(rules.len());
+    for rule in rules {
+      match definitions::expand(rule.re, defs) {
+        Ok(x) => expanded.push(x),
+        Err(reason) => return Err(gen.grammar_error(format!("lexical rule \"{}\": {}", rule.re, reason))),
+      }
+    }
+    let mut re = Vec::with_capacity(rules.len());
+    for (rule, (x, _)) in rules.iter().zip(expanded.iter()) {
+      match unicode::expand(x, g.raw.unicode) { Ok(x) => re.push(x), Err(reason) => return Err(gen.re2dfa_error(rule.re, reason)) }
+    }
+    let dfa = match re2dfa(re.iter().map(|x| &**x)) {
+      Ok(x) => x, Err((idx, reason)) => return Err(match &expanded[idx] {
+        (_, used) if used.is_empty() => gen.re2dfa_error(rules[idx].re, reason),
+        (x, used) => gen.re2dfa_error(rules[idx].re, format!("{} (after expanding definitions {} it is {})", reason, used.join(", "), x)),
+      })
+    };
+    if let Ok(rule_dfa) = re.iter().map(|x| re2dfa(std::iter::once(&**x))).collect::<std::result::Result<Vec<_>, _>>() {
+      for w in lex_check::check(rules, &rule_dfa, &dfa) { gen.lexical_warning(w); }
+    }
+    // the initial mode is checked in `Codegen::dfa`
+    if mode != 0 && (dfa.nodes.is_empty() || dfa.nodes[0].0.is_some()) {
+      return Err(gen.grammar_error(format!("lexer mode \"{}\" doesn't accept anything, or it accepts empty string", name)));
+    }
+    dfas.push((dfa, offset as u32));
+  }
+  let dfa = merge_dfa(dfas);
+  gen.dfa(&dfa)?;
   match algo {
     LL1 => gen.ll(g, LLCtx::new(g), &dfa),
     LALR1 | LR1 => {
@@ -99,4 +299,4 @@ pub fn work(mut raw: RawGrammar, algo: PGAlgo, gen: &mut impl Codegen) -> Result
       gen.lr1(g, &lr1, &dfa, orig_table, table, conflict)
     }
   }
-}
\ No newline at end of file
+}
@@ -0,0 +1,175 @@
+// a stable data model of the generated LR parser, for backends outside of this crate
+// it can be exported as JSON (`Lang::Json`), or rendered by a tera template (`Lang::Template`)
+// all ids and encodings are the same as the builtin table-driven backends, so their templates are good references
+use crate::{fmt, Codegen, Config, Lang};
+use common::{Grammar, HashMap, Placeholder, Quotes, ERR_IDX};
+use lalr1_core::{Conflict, ConflictKind, Table};
+use re2dfa::Dfa;
+use serde::Serialize;
+use std::io::{Result, Write};
+
+#[derive(Serialize)]
+pub struct Model<'a> {
+  // the code pieces in the grammar toml, which are inserted into the generated code verbatim
+  pub include: &'a str,
+  pub epilogue: Option<&'a str>,
+  pub lexer_field: &'a str,
+  pub lexer_action: &'a str,
+  pub parser_field: &'a str,
+  pub parser_def: Option<&'a str>,
+  // `terms[i]` is term i, term 0/1/2 are always `_Eps`/`_Eof`/`_Err`
+  pub terms: Vec<Term<'a>>,
+  // `nt[i]` is non-terminal i, the last one is the augmented start symbol, whose only production is the last production
+  pub nt: Vec<NonTerm<'a>>,
+  pub prod: Vec<Prod<'a>>,
+  // `action[state][term]` is `tag | (val << 2)`
+  // tag 0: shift and goto state `val`; tag 1: reduce with production `val`; tag 2: accept; tag 3: error
+  pub action: Vec<Vec<u32>>,
+  // `goto[state][nt]` is the state to goto after reducing to `nt` in `state`, or 0 if there is no such edge
+  pub goto: Vec<Vec<u32>>,
+  pub dfa: DfaModel,
+  // (term, the terms it can fall back to in order), see `RawGrammar::fallback`
+  pub fallback: Vec<(u32, Vec<u32>)>,
+  // the conflicts that can't be solved by priority and associativity, the first action in `action` is selected for them
+  pub conflict: Vec<ConflictModel>,
+}
+
+#[derive(Serialize)]
+pub struct Term<'a> {
+  pub name: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct NonTerm<'a> {
+  pub name: &'a str,
+  pub ty: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct Prod<'a> {
+  // the non-terminal id
+  pub lhs: u32,
+  pub rhs: Vec<Symbol<'a>>,
+  pub act: &'a str,
+  // the placeholders (`$$`, `$n`, `@n`, ...) in `act`, sorted by position, so that they can be rewritten by the backend
+  pub placeholders: Vec<PlaceholderModel>,
+}
+
+#[derive(Serialize)]
+pub struct Symbol<'a> {
+  pub name: &'a str,
+  pub is_term: bool,
+  // the term id or the non-terminal id
+  pub id: u32,
+  // the `ty` of the non-terminal, None for terms
+  pub ty: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+pub struct PlaceholderModel {
+  // `act[start..end]` is the placeholder, in bytes
+  pub start: usize,
+  pub end: usize,
+  // true for `@`, false for `$`
+  pub loc: bool,
+  // None is lhs, Some(n) is the n-th rhs symbol, starting from 1
+  pub index: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct DfaModel {
+  // `acc[state]` is the term accepted in `state`, or `_Err` if `state` is not accepting
+  pub acc: Vec<u32>,
+  // `ec[byte]` is the equivalence class of `byte`
+  pub ec: Vec<u8>,
+  // `edge[state][ec]` is the next state, state 0 is both the start state and the dead state, it has no incoming edge
+  pub edge: Vec<Vec<u32>>,
+}
+
+#[derive(Serialize)]
+pub struct ConflictModel {
+  pub state: u32,
+  pub term: u32,
+  // "shift-reduce", "reduce-reduce" or "many"
+  pub kind: &'static str,
+  // the same message reported by `on_conflict`
+  pub message: String,
+}
+
+impl<'a> Model<'a> {
+  pub fn new(g: &'a Grammar, table: &Table, dfa: &Dfa, conflict: &[Conflict]) -> Model<'a> {
+    let terms2id = g.terms.iter().enumerate().map(|(idx, t)| (t.name, idx as u32)).collect::<HashMap<_, _>>();
+    let symbol = |x: u32| match g.as_nt(x) {
+      Some(nt) => Symbol { name: g.nt[nt].name, is_term: false, id: nt as u32, ty: Some(g.nt[nt].ty) },
+      None => Symbol { name: g.terms[x as usize].name, is_term: true, id: x, ty: None },
+    };
+    let prod = g.prod.iter().map(|p| {
+      let rhs = p.rhs.iter().map(|&x| g.show_token(x as _)).collect::<Vec<_>>();
+      // already validated by `work` (see `Lang::quotes`)
+      let placeholders = common::placeholders(p.act, &rhs, g.raw.locations, Quotes::C).unwrap_or_default().into_iter().map(|(start, end, ph)|
+        match ph {
+          Placeholder::Val(index) => PlaceholderModel { start, end, loc: false, index },
+          Placeholder::Loc(index) => PlaceholderModel { start, end, loc: true, index },
+        }).collect();
+      Prod { lhs: p.lhs, rhs: p.rhs.iter().map(|&x| symbol(x)).collect(), act: p.act, placeholders }
+    }).collect();
+    let (action, goto) = (fmt::action_rows(g, table), fmt::goto_rows(g, table));
+    let dfa = DfaModel {
+      acc: dfa.nodes.iter().map(|&(acc, _)| acc.map(|x| terms2id[g.lexical[x as usize].term]).unwrap_or(ERR_IDX as u32)).collect(),
+      ec: dfa.ec.to_vec(),
+      edge: fmt::dfa_edge_rows(dfa),
+    };
+    let conflict = conflict.iter().zip(crate::show_lr::conflict(g, conflict)).map(|(c, message)| ConflictModel {
+      state: c.state,
+      term: c.ch,
+      kind: match c.kind { ConflictKind::SR { .. } => "shift-reduce", ConflictKind::RR { .. } => "reduce-reduce", ConflictKind::Many(_) => "many" },
+      message,
+    }).collect();
+    Model {
+      include: g.raw.include,
+      epilogue: g.raw.epilogue,
+      lexer_field: g.raw.lexer_field,
+      lexer_action: g.raw.lexer_action,
+      parser_field: g.raw.parser_field,
+      parser_def: g.raw.parser_def,
+      terms: g.terms.iter().map(|t| Term { name: t.name }).collect(),
+      nt: g.nt.iter().map(|nt| NonTerm { name: nt.name, ty: nt.ty }).collect(),
+      prod,
+      action,
+      goto,
+      dfa,
+      fallback: g.fallback.clone(),
+      conflict,
+    }
+  }
+}
+
+impl<W: Write> Config<'_, W> {
+  pub fn model_lalr1(&mut self, g: &Grammar, table: &Table, dfa: &Dfa, conflict: &[Conflict]) -> Result<()> {
+    let model = Model::new(g, table, dfa, conflict);
+    if self.lang == Lang::Json { self.model_json(&model) } else { self.model_template(&model) }
+  }
+
+  #[cfg(feature = "json")]
+  fn model_json(&mut self, model: &Model) -> Result<()> { Ok(serde_json::to_writer_pretty(&mut self.code_output, model)?) }
+
+  #[cfg(not(feature = "json"))]
+  fn model_json(&mut self, _model: &Model) -> Result<()> {
+    Err(self.grammar_error("JSON output requires the `json` feature of parser-gen".to_owned()))
+  }
+
+  #[cfg(feature = "tera")]
+  fn model_template(&mut self, model: &Model) -> Result<()> {
+    let template = std::fs::read_to_string(self.template)?;
+    let ctx = tera::Context::from_serialize(model).expect("the model should be serializable");
+    match tera::Tera::one_off(&template, &ctx, false) {
+      Ok(code) => self.code_output.write_all(code.as_bytes()),
+      Err(e) => Err(self.grammar_error(format!("failed to render template \"{}\": {:?}", self.template, e))),
+    }
+  }
+
+  #[cfg(not(feature = "tera"))]
+  fn model_template(&mut self, _model: &Model) -> Result<()> {
+    Err(self.grammar_error("templates require the `tera` feature of parser-gen".to_owned()))
+  }
+}
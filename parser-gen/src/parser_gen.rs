@@ -1,23 +1,43 @@
 use clap::{App, Arg};
-use std::{io, fs};
+use std::{io, fs, path::Path};
 use parser_gen::*;
+use common::{RawGrammar, RawLexer};
 
 fn main() -> io::Result<()> {
   let m = App::new("parser_gen")
     .author("MashPlant").about("Read config from a toml file, and generate a parser in various language")
     .arg(Arg::new("input").required(true))
     .arg(Arg::new("output").long("output").short('o').takes_value(true).required(true).value_name("path"))
-    .arg(Arg::new("lang").long("lang").short('l').takes_value(true).possible_values(&["rs", "cpp", "java"]).required(true))
+    .arg(Arg::new("lang").long("lang").short('l').takes_value(true).possible_values(&["rs", "cpp", "java", "c", "python", "ts", "go", "json", "template"]).required(true))
+    .arg(Arg::new("algo").long("algo").short('g').takes_value(true).possible_values(&["lalr1", "lr1", "ll1"]).default_value("lalr1"))
     .arg(Arg::new("verbose").long("verbose").takes_value(true).value_name("path").help("Print some parser information (ll table or lr fsm) to the path"))
     .arg(Arg::new("show_fsm").long("show_fsm").takes_value(true).value_name("path").help("Print lr fsm in dot file format to the path"))
     .arg(Arg::new("show_dfa").long("show_dfa").takes_value(true).value_name("path").help("Print dfa in dot file format to the path"))
     .arg(Arg::new("log_token").long("log_token").help("Make parser print recognized token"))
     .arg(Arg::new("log_reduce").long("log_reduce").help("Make parser print the rule used when reducing"))
     .arg(Arg::new("use_unsafe").long("use_unsafe").help("Make parser use some unsafe operations to improve speed"))
+    .arg(Arg::new("recursive_descent").long("recursive_descent").help("Generate a recursive descent parser for LL(1) grammar (only for rust)"))
+    .arg(Arg::new("direct_lr").long("direct_lr").help("Generate a directly-coded LR parser, instead of a table-driven one (only for rust)"))
+    .arg(Arg::new("direct_lexer").long("direct_lexer").help("Generate a directly-coded lexer, instead of a table-driven one (only for rust and cpp)"))
+    .arg(Arg::new("header").long("header").takes_value(true).value_name("path").help("Write the declarations to a separate header file at the path (only for c)"))
+    .arg(Arg::new("prefix").long("prefix").takes_value(true).default_value("").help("Prepend the prefix to all global names (only for c)"))
+    .arg(Arg::new("package").long("package").takes_value(true).default_value("parser").help("The name of the generated package (only for go)"))
+    .arg(Arg::new("template").long("template").takes_value(true).value_name("path").required_if_eq("lang", "template").help("Render the parser with the tera template at the path (only for template)"))
     .get_matches();
   let output = fs::File::create(m.value_of("output").unwrap())
     .expect("failed to open output file");
   let output = io::BufWriter::new(output);
+  // the generated source includes the header by its file name, so they should be in the same directory
+  let header_output = m.value_of("header").map(|path| {
+    let name = std::path::Path::new(path).file_name().and_then(|x| x.to_str()).expect("invalid header path");
+    (name, io::BufWriter::new(fs::File::create(path).expect("failed to open header file")))
+  });
+  let lang = match m.value_of("lang") {
+    Some("rs") => Lang::Rs, Some("cpp") => Lang::Cpp, Some("java") => Lang::Java,
+    Some("c") => Lang::C, Some("python") => Lang::Python, Some("ts") => Lang::TypeScript, Some("go") => Lang::Go,
+    Some("json") => Lang::Json, Some("template") => Lang::Template,
+    _ => unreachable!()
+  };
   let mut cfg = Config {
     verbose: m.value_of("verbose"),
     show_fsm: m.value_of("show_fsm"),
@@ -25,15 +45,30 @@ fn main() -> io::Result<()> {
     log_token: m.is_present("log_token"),
     log_reduce: m.is_present("log_reduce"),
     use_unsafe: m.is_present("use_unsafe"),
-    lang: match m.value_of("lang") {
-      Some("rs") => Lang::Rs, Some("cpp") => Lang::Cpp, Some("java") => Lang::Java,
-      _ => unreachable!()
-    },
+    recursive_descent: m.is_present("recursive_descent"),
+    direct_lr: m.is_present("direct_lr"),
+    direct_lexer: m.is_present("direct_lexer"),
     on_conflict: |c| eprintln!("{}", c),
-    code_output: output,
+    on_lexical_warning: |w| eprintln!("{}", w),
+    header_output,
+    prefix: m.value_of("prefix").unwrap(),
+    package: m.value_of("package").unwrap(),
+    template: m.value_of("template").unwrap_or(""),
+    ..Config::new(lang, output)
+  };
+  let input_path = m.value_of("input").unwrap();
+  let input = fs::read_to_string(input_path)?;
+  let lex;
+  let mut raw = toml::from_str::<RawGrammar>(&input).expect("invalid grammar toml");
+  // `lex_path` is relative to the grammar toml
+  if let Some(p) = raw.lex_path {
+    lex = fs::read_to_string(Path::new(input_path).parent().unwrap().join(p))?;
+    toml::from_str::<RawLexer>(&lex).expect("invalid lexer toml").apply(&mut raw);
+  }
+  let algo = match m.value_of("algo") {
+    Some("lalr1") => PGAlgo::LALR1, Some("lr1") => PGAlgo::LR1, Some("ll1") => PGAlgo::LL1,
+    _ => unreachable!()
   };
-  let input = fs::read_to_string(m.value_of("input").unwrap())?;
-  let raw = toml::from_str(&input).expect("invalid grammar toml");
-  work(raw, PGAlgo::LALR1, &mut cfg).expect("failed to generate code");
+  work(raw, algo, &mut cfg).expect("failed to generate code");
   Ok(())
 }
\ No newline at end of file
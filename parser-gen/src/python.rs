@@ -0,0 +1,62 @@
+use crate::*;
+
+impl<W: std::io::Write> Config<'_, W> {
+  // one method `_act{i}` for each production, values are untyped, so no `StackItem` is needed
+  fn py_act<'a>(g: &'a Grammar) -> impl Display + 'a {
+    fmt_::fn2display(move |f| {
+      for (i, prod) in g.prod.iter().enumerate() {
+        writeln!(f, "  def _act{}(self, lexer, token, stk):", i)?;
+        for j in (0..prod.rhs.len()).rev() {
+          match prod.args {
+            Some(args) => writeln!(f, "    {} = stk.pop()[0]", args[j].0)?,
+            None => writeln!(f, "    _{} = stk.pop()[0]", j + 1)?,
+          }
+        }
+        f.write_str("    __ = None\n")?;
+        if i == g.prod.len() - 1 {
+          f.write_str("    __ = _1\n")?;
+        } else {
+          let act = fmt::act(g, prod, Lang::Python);
+          if !act.trim().is_empty() { writeln!(f, "{}", fmt::indent(&act, "    ", ""))?; }
+        }
+        f.write_str("    return __\n\n")?;
+      }
+      Ok(())
+    })
+  }
+
+  pub fn py_lalr1(&mut self, g: &Grammar, table: &Table, dfa: &Dfa) -> Result<()> {
+    let name = |t: u32| g.terms[t as usize].name;
+    write!(
+      self.code_output, include_str!("template/lalr1.py.template"),
+      include = g.raw.include,
+      token_kind = fmt_::fn2display(|f| {
+        for (i, t) in g.terms.iter().enumerate() { writeln!(f, "  {} = {}", t.name, i)?; }
+        Ok(())
+      }),
+      acc = fmt::acc(g, dfa, "TokenKind."),
+      ec = fmt::comma_sep(dfa.ec.iter()),
+      dfa_edge = fmt::dfa_edge(dfa, ('(', ')')),
+      lexer_field = fmt::indent(g.raw.lexer_field, "    ", ""),
+      lexer_action = fmt::indent(g.raw.lexer_action, "          ", ""),
+      col_step = fmt_::fn2display(move |f| {
+        // utf-8 continuation bytes are not counted, like `fmt::col_step`
+        let step = if g.raw.unicode { "(ch & 0xC0 != 0x80)" } else { "1" };
+        match g.raw.tab_width {
+          Some(w) => write!(f, "self.col += {0} - (self.col - 1) % {0} if ch == 9 else {1}", w, step),
+          None => write!(f, "self.col += {}", step),
+        }
+      }),
+      parser_type = g.raw.parser_def.unwrap_or("Parser"),
+      prod = fmt::comma_sep(g.prod.iter().map(|x| x.lhs)),
+      action = fmt::action(g, table, ('(', ')')),
+      goto = fmt::goto(g, table, ('(', ')')),
+      fallback = fmt::comma_sep(g.fallback.iter().map(move |(from, to)|
+        format!("TokenKind.{}: ({},)", name(*from), fmt::comma_sep(to.iter().map(|&x| format!("TokenKind.{}", name(x))))))),
+      parser_field = fmt::indent(g.raw.parser_field, "    ", "pass"),
+      parser_act = Self::py_act(g),
+      act_list = fmt::comma_sep((0..g.prod.len()).map(|i| format!("_act{}", i))),
+      epilogue = g.raw.epilogue.unwrap_or(""),
+    )
+  }
+}
@@ -1,8 +1,9 @@
 use crate::*;
 
 impl<W: std::io::Write> Config<'_, W> {
-  fn rs_common(&mut self, g: &Grammar, dfa: &Dfa, types: &[&str], stack_need_fail: bool) -> Result<()> {
-    let verbose = self.verbose.is_some();
+  // types == None: the parser doesn't need a `StackItem` enum
+  fn rs_common(&mut self, g: &Grammar, dfa: &Dfa, types: Option<&[&str]>, stack_need_fail: bool) -> Result<()> {
+    let (verbose, direct_lexer, use_unsafe) = (self.verbose.is_some(), self.direct_lexer, self.use_unsafe);
     write!(
       self.code_output, include_str!("template/common.rs.template"),
       include = g.raw.include,
@@ -12,19 +13,54 @@ impl<W: std::io::Write> Config<'_, W> {
         "macro_rules!idx{($arr:expr,$idx:expr)=>{&$arr[$idx]};}macro_rules!err{()=>{unreachable!()};}"
       },
       token_kind = fmt::comma_sep(g.terms.iter().map(|x| x.name)),
-      stack_item = fmt_::fn2display(move |f| {
+      stack_item = fmt_::fn2display(move |f| if let Some(types) = types {
+        f.write_str("enum StackItem<'p> { _Token(Token<'p>), ")?;
         if stack_need_fail { f.write_str("_Fail,")?; }
+        // the user error of a fallible action in table-driven LL(1), `_parse` should return it directly
+        if let (true, Some(e)) = (stack_need_fail, g.raw.error_ty) { write!(f, "_User({}),", e)?; }
         for (i, ty) in types.iter().enumerate() { write!(f, "_{}({}),", i, ty)?; }
-        Ok(())
+        f.write_str("}")
+      } else { Ok(()) }),
+      parse_error = fmt_::fn2display(move |f| if let Some(e) = g.raw.error_ty {
+        write!(f, "#[derive(Debug)]pub enum ParseError<'l>{{Syntax(Token<'l>),User({})}}\
+          impl<'l> From<Token<'l>> for ParseError<'l>{{fn from(t:Token<'l>)->Self{{ParseError::Syntax(t)}}}}", e)
+      } else { Ok(()) }),
+      lexer_field = fmt::lexer_field(g, Lang::Rs),
+      mode_start = fmt::mode_start(g, Lang::Rs),
+      next_name = fmt::next_name(g),
+      layout = fmt::layout(g, Lang::Rs),
+      mode_decl = fmt::mode_last_state(g, Lang::Rs).0,
+      mode_acc = fmt::mode_last_state(g, Lang::Rs).1,
+      mode_trans = fmt::mode_trans(g, dfa, Lang::Rs),
+      dfa_table = fmt_::fn2display(move |f| if direct_lexer { Ok(()) } else {
+        writeln!(f, "static ACC: [TokenKind; {}] = [{}];", dfa.nodes.len(), fmt::acc(g, dfa, "TokenKind::"))?;
+        writeln!(f, "static EC: [u8; 256] = [{}];", fmt::comma_sep(dfa.ec.iter()))?;
+        write!(f, "static DFA_EDGE: [[{}; {}]; {}] = [{}];", fmt::min_u(dfa.nodes.len()), dfa.ec_num, dfa.nodes.len(), fmt::dfa_edge(dfa, ('[', ']')))
+      }),
+      dfa_next = fmt_::fn2display(move |f| if direct_lexer { write!(f, "{}", fmt::dfa_next_code(dfa, Lang::Rs)) } else {
+        f.write_str("*idx!(idx!(DFA_EDGE, state), *idx!(EC, ch) as usize) as usize")
+      }),
+      dfa_acc = fmt_::fn2display(move |f| if direct_lexer { write!(f, "{}", fmt::dfa_acc_code(g, dfa, Lang::Rs)) } else {
+        f.write_str("*idx!(ACC, nxt)")
       }),
-      lexer_field = g.raw.lexer_field,
-      dfa_size = dfa.nodes.len(),
-      acc = fmt::acc(g, dfa, "TokenKind"),
-      ec = fmt::comma_sep(dfa.ec.iter()),
-      u_dfa_size = fmt::min_u(dfa.nodes.len()),
-      ec_num = dfa.ec_num,
-      dfa_edge = fmt::dfa_edge(dfa, ('[', ']')),
       lexer_action = g.raw.lexer_action,
+      col_step = fmt::col_step(g, Lang::Rs),
+      // if `piece` is not valid utf-8 (e.g., in an error token), `piece_str` returns its valid prefix
+      token_impl = fmt_::fn2display(move |f| {
+        if g.raw.unicode {
+          write!(f, "impl<'l> Token<'l>{{pub fn piece_str(&self)->&'l str{{match std::str::from_utf8(self.piece){{Ok(s)=>s,Err(e)=>{}}}}}}}",
+            if use_unsafe { "unsafe{std::str::from_utf8_unchecked(&self.piece[..e.valid_up_to()])}" } else { "std::str::from_utf8(&self.piece[..e.valid_up_to()]).unwrap()" })?;
+        }
+        // `lo..hi` is the byte range in the input, `line` & `col` is the beginning
+        // `Span::of(t, end)` is the span of token `t` that ends at `end`, `a.to(b)` is from the beginning of `a` to the end of `b`
+        if g.raw.locations {
+          f.write_str("#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]pub struct Span{pub lo:usize,pub hi:usize,pub line:u32,pub col:u32}\
+            impl Span{pub fn of(t:&Token,end:usize)->Span{Span{lo:end-t.piece.len(),hi:end,line:t.line,col:t.col}}\
+            pub fn to(self,r:Span)->Span{Span{hi:r.hi,..self}}}")?;
+        }
+        Ok(())
+      }),
+      pos_step = if g.raw.locations { "self.pos += i;" } else { "" },
       show_token_prod = fmt_::fn2display(move |f| if verbose {
         f.write_str("fn show_token(id:u32)->&'static str{[")?;
         for i in 0..g.token_num() { write!(f, "{:?}, ", g.show_token(i))?; }
@@ -41,9 +77,37 @@ impl<W: std::io::Write> Config<'_, W> {
     )
   }
 
+  // define `_loc` & `_locn` for production `prod` from the location stack `locs` of LR parser, and replace the rhs locations with `_loc`
+  // an empty production is located at the beginning of the lookahead token
+  fn loc_bind<'a>(g: &'a Grammar, prod: &'a Prod) -> impl Display + 'a {
+    fmt_::fn2display(move |f| if g.raw.locations {
+      let n = prod.rhs.len();
+      for j in 1..=n { write!(f, "let _loc{}=*idx!(locs,locs.len()-{});", j, n - j + 1)?; }
+      if n == 0 {
+        writeln!(f, "let _loc={{let s=Span::of(&token,lexer.pos);Span{{hi:s.lo,..s}}}};locs.push(_loc);")
+      } else {
+        writeln!(f, "let _loc=_loc1.to(_loc{});locs.truncate(locs.len()-{});locs.push(_loc);", n, n)
+      }
+    } else { Ok(()) })
+  }
+
+  // the error type of `parse`, a syntax error token can be converted to it by `into()`
+  fn err_type(g: &Grammar) -> &'static str { if g.raw.error_ty.is_some() { "ParseError<'l>" } else { "Token<'l>" } }
+
+  // convert the token variable `tk` of a term to its value by `conv` (see `RawTermValue`), return an `_Err` token if it fails
+  fn term_conv<'a>(conv: &'a str, tk: &'a str) -> impl Display + 'a {
+    fmt_::fn2display(move |f| write!(f, "match{{let piece={0}.piece;{{{1}}}}}{{Ok(v)=>v,Err(_)=>return Err(Token{{kind:TokenKind::_Err,..{0}}}.into())}}", tk, conv))
+  }
+
+  // a fallible action is called as a closure, so that `?` and `return` in it leave the action instead of the parser
+  fn fallible_act<'a>(g: &'a Grammar, prod: &'a Prod) -> impl Display + 'a {
+    fmt_::fn2display(move |f| write!(f, "(||->Result<{},{}>{{{}}})()", g.nt[prod.lhs as usize].ty, g.raw.error_ty.unwrap_or("_"), fmt::act(g, prod, Lang::Rs)))
+  }
+
   // log_reduce == self.log_reduce, but this functions cannot borrow self
   // is_pair == true: `stk` is Vec<(StackItem, integer)>; is_pair == false: `stk` is Vec<StackItem>
-  fn gen_act<'a>(log_reduce: bool, g: &'a Grammar, types2id: HashMap<&'a str, u32>, is_pair: bool, handle_err: &'a str) -> impl std::fmt::Display + 'a {
+  // `user_err` handles the error `e` of a fallible action
+  fn gen_act<'a>(log_reduce: bool, g: &'a Grammar, types2id: HashMap<&'a str, u32>, is_pair: bool, handle_err: &'a str, user_err: &'a str) -> impl std::fmt::Display + 'a {
     fmt_::fn2display(move |f| {
       let pat = if is_pair { ",_" } else { "" };
       for (i, prod) in g.prod.iter().enumerate() {
@@ -51,18 +115,25 @@ impl<W: std::io::Write> Config<'_, W> {
         if log_reduce {
           writeln!(f, r#"println!("{}");"#, g.show_prod(i, None))?;
         }
+        write!(f, "{}", Self::loc_bind(g, prod))?;
         for (j, &x) in prod.rhs.iter().enumerate().rev() {
           let name = fmt_::fn2display(move |f|
             match prod.args { Some(args) => f.write_str(args[j].0), None => write!(f, "_{}", j + 1) });
           if let Some(x) = g.as_nt(x) {
             let id = types2id[g.nt[x].ty];
             writeln!(f, "let {}=match stk.pop(){{Some((StackItem::_{}(x){}))=>x,_=>{}}};", name, id, pat, handle_err)?;
+          } else if let Some((ty, _)) = g.terms[x as usize].value {
+            writeln!(f, "let {}=match stk.pop(){{Some((StackItem::_{}(x){}))=>x,_=>{}}};", name, types2id[ty], pat, handle_err)?;
           } else {
             writeln!(f, "let {}=match stk.pop(){{Some((StackItem::_Token(x){}))=>x,_=>{}}};", name, pat, handle_err)?;
           }
         }
         let id = types2id[g.nt[prod.lhs as usize].ty];
-        writeln!(f, "StackItem::_{}({{{}}})}}", id, prod.act)?;
+        if prod.fallible {
+          writeln!(f, "StackItem::_{}(match {}{{Ok(v)=>v,Err(e)=>{}}})}}", id, Self::fallible_act(g, prod), user_err)?;
+        } else {
+          writeln!(f, "StackItem::_{}({{{}}})}}", id, fmt::act(g, prod, Lang::Rs))?;
+        }
       }
       Ok(())
     })
@@ -72,15 +143,17 @@ impl<W: std::io::Write> Config<'_, W> {
 impl<W: std::io::Write> Config<'_, W> {
   // return None if `rs_common` returns None, you can check the doc of `rs_common`
   pub fn rs_lalr1(&mut self, g: &Grammar, table: &Table, dfa: &Dfa) -> Result<()> {
+    if self.direct_lr { return self.rs_lalr1_direct(g, table, dfa); }
     let (types, types2id) = fmt::gather_types(g);
     let parse_res = g.nt.last().unwrap().ty;
     let res_id = types2id[parse_res];
-    self.rs_common(g, dfa, &types, false)?;
+    self.rs_common(g, dfa, Some(&types), false)?;
     write!(
       self.code_output, include_str!("template/lalr1.rs.template"),
       u_lr_fsm_size = fmt::min_u(table.len()),
       parser_type = g.raw.parser_def.unwrap_or("Parser"),
       res_type = parse_res,
+      err_type = Self::err_type(g),
       res_id = res_id,
       prod_size = g.prod.len(),
       prod = fmt::comma_sep(g.prod.iter().map(|x| x.lhs)),
@@ -90,16 +163,128 @@ impl<W: std::io::Write> Config<'_, W> {
       u_act_size = fmt::min_u(table.len() * 4),
       action = fmt::action(g, table, ('[', ']')),
       goto = fmt::goto(g, &table, ('[', ']')),
-      parser_act = Self::gen_act(self.log_reduce, g, types2id, true, "err!()"),
+      shift_item = fmt_::fn2display(|f| {
+        let mut valued = g.terms.iter().filter_map(|t| t.value.map(|v| (t.name, v))).peekable();
+        if valued.peek().is_none() { return f.write_str("StackItem::_Token(token)"); }
+        f.write_str("match token.kind{")?;
+        for (name, (ty, conv)) in valued {
+          write!(f, "TokenKind::{}=>StackItem::_{}({}),", name, types2id[ty], Self::term_conv(conv, "token"))?;
+        }
+        f.write_str("_=>StackItem::_Token(token),}")
+      }),
+      parser_act = Self::gen_act(self.log_reduce, g, types2id.clone(), true, "err!()", "return Err(ParseError::User(e))"),
       log_token = if self.log_token { r#"println!("{:?}",token);"# } else { "" },
+      on_error = fmt::lr_error(g, Lang::Rs),
+      loc_decl = if g.raw.locations { "let mut locs: Vec<Span> = Vec::new();" } else { "" },
+      loc_shift = if g.raw.locations { "locs.push(Span::of(&token, lexer.pos));" } else { "" },
+    )
+  }
+
+  // encode ACTION & GOTO as code, in one loop dispatching on the top state (not recursive ascent with per-state functions)
+  // there is one value stack for each type, so `StackItem` is not needed
+  fn rs_lalr1_direct(&mut self, g: &Grammar, table: &Table, dfa: &Dfa) -> Result<()> {
+    let (types, types2id) = fmt::gather_types(g);
+    let res_id = types2id[g.nt.last().unwrap().ty];
+    self.rs_common(g, dfa, None, false)?;
+    let (log_token, log_reduce) = (self.log_token, self.log_reduce);
+    let log_token = if log_token { r#"println!("{:?}",token);"# } else { "" };
+    let loc_shift = if g.raw.locations { "locs.push(Span::of(&token,lexer.pos));" } else { "" };
+    let types2id = &types2id;
+    write!(
+      self.code_output, include_str!("template/lalr1_direct.rs.template"),
+      parser_type = g.raw.parser_def.unwrap_or("Parser"),
+      res_type = g.nt.last().unwrap().ty,
+      err_type = Self::err_type(g),
+      u_lr_fsm_size = fmt::min_u(table.len()),
+      value_stk = fmt_::fn2display(move |f| {
+        for (i, ty) in types.iter().enumerate() { writeln!(f, "let mut stk{}:Vec<{}>=Vec::new();", i, ty)?; }
+        if g.raw.locations { f.write_str("let mut locs:Vec<Span>=Vec::new();")?; }
+        Ok(())
+      }),
+      log_token = log_token,
+      state_act = fmt_::fn2display(move |f| {
+        for (i, TableEntry { act, .. }) in table.iter().enumerate() {
+          // sort them to make the output stable
+          let mut act = act.iter().filter_map(|(&ch, acts)| acts.get(0).map(|&a| (ch, a))).collect::<Vec<_>>();
+          act.sort_unstable_by_key(|&(ch, _)| ch);
+          writeln!(f, "{}=>match token.kind{{", i)?;
+          let mut reduce = HashMap::default();
+          for &(ch, a) in &act {
+            match a {
+              Act::Shift(s) => match g.terms.get(ch as usize).and_then(|t| t.value) {
+                Some((ty, conv)) => writeln!(f, "TokenKind::{}=>{{states.push({});stk{}.push({});{}token=lexer.next();{}continue;}}",
+                  g.show_token(ch as _), s, types2id[ty], Self::term_conv(conv, "token"), loc_shift, log_token)?,
+                None => writeln!(f, "TokenKind::{}=>{{states.push({});stk_token.push(token);{}token=lexer.next();{}continue;}}",
+                  g.show_token(ch as _), s, loc_shift, log_token)?,
+              },
+              Act::Reduce(r) => reduce.entry(r).or_insert_with(Vec::new).push(ch),
+              Act::Acc => writeln!(f, "TokenKind::{}=>return Ok(match stk{}.pop(){{Some(r)=>r,None=>err!()}}),", g.show_token(ch as _), res_id)?,
+            }
+          }
+          let mut reduce = reduce.into_iter().collect::<Vec<_>>();
+          reduce.sort_unstable();
+          for (r, chs) in reduce {
+            let chs = fmt_::sep(chs.into_iter().map(move |ch| fmt_::fn2display(move |f| write!(f, "TokenKind::{}", g.show_token(ch as _)))), "|");
+            writeln!(f, "{}=>{},", chs, r)?;
+          }
+          // the fallback term is decided statically, because the valid terms of this state are known
+          for (from, to) in &g.fallback {
+            if act.iter().any(|x| x.0 == *from) { continue; }
+            if let Some(&to) = to.iter().find(|&&to| act.iter().any(|x| x.0 == to)) {
+              writeln!(f, "TokenKind::{}=>{{token.kind=TokenKind::{};continue;}}", g.show_token(*from as _), g.show_token(to as _))?;
+            }
+          }
+          f.write_str("_=>return Err(token.into()),},\n")?;
+        }
+        Ok(())
+      }),
+      parser_act = fmt_::fn2display(move |f| {
+        for (i, prod) in g.prod.iter().enumerate() {
+          write!(f, "{}=>{{", i)?;
+          if log_reduce {
+            writeln!(f, r#"println!("{}");"#, g.show_prod(i, None))?;
+          }
+          write!(f, "{}", Self::loc_bind(g, prod))?;
+          for (j, &x) in prod.rhs.iter().enumerate().rev() {
+            let name = fmt_::fn2display(move |f|
+              match prod.args { Some(args) => f.write_str(args[j].0), None => write!(f, "_{}", j + 1) });
+            let stk = fmt_::fn2display(move |f| match (g.as_nt(x), g.terms.get(x as usize).and_then(|t| t.value)) {
+              (Some(x), _) => write!(f, "stk{}", types2id[g.nt[x].ty]),
+              (_, Some((ty, _))) => write!(f, "stk{}", types2id[ty]),
+              _ => f.write_str("stk_token"),
+            });
+            writeln!(f, "let {}=match {}.pop(){{Some(x)=>x,None=>err!()}};", name, stk)?;
+          }
+          let lhs = prod.lhs as usize;
+          if prod.fallible {
+            writeln!(f, "let value=match {}{{Ok(v)=>v,Err(e)=>return Err(ParseError::User(e))}};", Self::fallible_act(g, prod))?;
+          } else {
+            writeln!(f, "let value={{{}}};", fmt::act(g, prod, Lang::Rs))?;
+          }
+          writeln!(f, "stk{}.push(value);states.truncate(states.len()-{});", types2id[g.nt[lhs].ty], prod.rhs.len())?;
+          let mut goto = HashMap::default();
+          for (state, t) in table.iter().enumerate() {
+            if let Some(&to) = t.goto.get(&((lhs + g.terms.len()) as u32)) {
+              goto.entry(to).or_insert_with(Vec::new).push(state);
+            }
+          }
+          let mut goto = goto.into_iter().collect::<Vec<_>>();
+          goto.sort_unstable();
+          f.write_str("match *idx!(states,states.len()-1){")?;
+          for (to, from) in goto { writeln!(f, "{}=>{},", fmt_::sep(from.iter(), "|"), to)?; }
+          f.write_str("_=>err!(),}}\n")?;
+        }
+        Ok(())
+      }),
     )
   }
 
   pub fn rs_ll1(&mut self, g: &Grammar, ll: &LLCtx, dfa: &Dfa) -> Result<()> {
+    if self.recursive_descent { return self.rs_ll1_rd(g, ll, dfa); }
     let (types, types2id) = fmt::gather_types(g);
     let parse_res = g.nt.last().unwrap().ty;
     let res_id = types2id[parse_res];
-    self.rs_common(g, dfa, &types, true)?;
+    self.rs_common(g, dfa, Some(&types), true)?;
     write!(
       self.code_output, include_str!("template/ll1.rs.template"),
       term_num = g.terms.len(),
@@ -118,10 +303,91 @@ impl<W: std::io::Write> Config<'_, W> {
         f.write_str("),\n")?;
       }, Ok(())).1),
       parser_type = g.raw.parser_def.unwrap_or("Parser"),
-      parser_act = Self::gen_act(self.log_reduce, g, types2id, false, "return StackItem::_Fail"),
-      res_type = parse_res,
+      parser_act = Self::gen_act(self.log_reduce, g, types2id, false, "return StackItem::_Fail", "return StackItem::_User(e)"),
+      parse_ret = fmt_::fn2display(move |f| if g.raw.error_ty.is_some() {
+        write!(f, "Result<{}, ParseError<'l>>", parse_res)
+      } else { write!(f, "Option<{}>", parse_res) }),
+      parse_ok = if g.raw.error_ty.is_some() { "Ok(parse_res)" } else { "Some(parse_res)" },
+      // the token where `_parse` stops is reported as the syntax error
+      parse_err = if g.raw.error_ty.is_some() {
+        "StackItem::_User(e) => Err(ParseError::User(e)), _ => Err(ParseError::Syntax(token))"
+      } else { "_ => None" },
       res_nt_id = g.token_num() - 1,
       res_id = res_id
     )
   }
+
+  // generate one function `_parse_{nt name}` for each non-terminal, the values are passed directly without `StackItem`
+  fn rs_ll1_rd(&mut self, g: &Grammar, ll: &LLCtx, dfa: &Dfa) -> Result<()> {
+    self.rs_common(g, dfa, None, false)?;
+    let (log_token, log_reduce) = (self.log_token, self.log_reduce);
+    let log_token = if log_token { r#"println!("{:?}",token);"# } else { "" };
+    // with locations, each `_parse_{nt name}` also returns the span of this non-terminal
+    let loc = g.raw.locations;
+    write!(
+      self.code_output, include_str!("template/ll1_rd.rs.template"),
+      parser_type = g.raw.parser_def.unwrap_or("Parser"),
+      res_type = g.nt.last().unwrap().ty,
+      err_type = Self::err_type(g),
+      start = g.raw.start,
+      res_loc = if loc { ".0" } else { "" },
+      log_token = log_token,
+      parse_fn = fmt_::fn2display(move |f| {
+        // the last non-terminal is START_NT_NAME, it is handled in `parse`
+        for (nt, (nt_info, table)) in g.nt.iter().zip(ll.table.iter()).enumerate().take(g.nt.len() - 1) {
+          writeln!(f, "#[allow(non_snake_case, unused_variables)] fn _parse_{}<'l: 'p>(&mut self, token: &mut Token<'l>, lexer: &mut Lexer<'l>) -> Result<{}{}{}, {}> {{",
+            nt_info.name, if loc { "(" } else { "" }, nt_info.ty, if loc { ",Span)" } else { "" }, Self::err_type(g))?;
+          f.write_str("match token.kind {\n")?;
+          for prod in g.get_prod(nt) {
+            // if there are conflicts, the first production is selected (which is consistent with `show_ll::table`)
+            let mut predict = table.iter().filter(|(_, prods)| prods[0] == prod.id).map(|(&ch, _)| ch).collect::<Vec<_>>();
+            if predict.is_empty() { continue; }
+            predict.sort_unstable();
+            let predict = fmt_::sep(predict.iter().map(|&ch| fmt_::fn2display(move |f| write!(f, "TokenKind::{}", g.show_token(ch as _)))), "|");
+            writeln!(f, "{}=>{{", predict)?;
+            if log_reduce {
+              writeln!(f, r#"println!("{}");"#, g.show_prod(prod.id as _, None))?;
+            }
+            for (j, &x) in prod.rhs.iter().enumerate() {
+              let name = fmt_::fn2display(move |f|
+                match prod.args { Some(args) => f.write_str(args[j].0), None => write!(f, "_{}", j + 1) });
+              if let Some(x) = g.as_nt(x) {
+                if loc {
+                  // `name` may be a typed pattern like `x: T`, which can't appear in a tuple pattern
+                  writeln!(f, "let (v,_loc{})=self._parse_{}(token,lexer)?;let {}=v;", j + 1, g.nt[x].name, name)?;
+                } else {
+                  writeln!(f, "let {}=self._parse_{}(token,lexer)?;", name, g.nt[x].name)?;
+                }
+                continue;
+              }
+              if loc { writeln!(f, "let _loc{}=Span::of(token,lexer.pos);", j + 1)?; }
+              if let Some((_, conv)) = g.terms[x as usize].value {
+                writeln!(f, "let {}=if token.kind==TokenKind::{}{{let t=*token;let v={};*token=lexer.next();{}v}}else{{return Err((*token).into())}};",
+                  name, g.show_token(x as _), Self::term_conv(conv, "t"), log_token)?;
+              } else {
+                writeln!(f, "let {}=if token.kind==TokenKind::{}{{let t=*token;*token=lexer.next();{}t}}else{{return Err((*token).into())}};",
+                  name, g.show_token(x as _), log_token)?;
+              }
+            }
+            match prod.rhs.len() {
+              _ if !loc => {}
+              // an empty production is located at the beginning of the lookahead token
+              0 => f.write_str("let _loc={let s=Span::of(token,lexer.pos);Span{hi:s.lo,..s}};")?,
+              n => writeln!(f, "let _loc=_loc1.to(_loc{});", n)?,
+            }
+            let ret = if loc { ".map(|v|(v,_loc))" } else { "" };
+            if prod.fallible {
+              writeln!(f, "{}{}.map_err(ParseError::User)}}", Self::fallible_act(g, prod), ret)?;
+            } else if loc {
+              writeln!(f, "Ok(({{{}}},_loc))}}", fmt::act(g, prod, Lang::Rs))?;
+            } else {
+              writeln!(f, "Ok({{{}}})}}", fmt::act(g, prod, Lang::Rs))?;
+            }
+          }
+          f.write_str("_=>Err((*token).into()),}}\n")?;
+        }
+        Ok(())
+      })
+    )
+  }
 }
\ No newline at end of file
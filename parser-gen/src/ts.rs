@@ -0,0 +1,96 @@
+use crate::*;
+
+// the typed array that can hold numbers < x
+fn min_u_arr(x: usize) -> &'static str {
+  match fmt::min_u(x) { "u8" => "Uint8Array", "u16" => "Uint16Array", _ => "Uint32Array" }
+}
+
+// compress the 2-d table by row displacement, return a `Comb` in the generated code
+// entry (r, c) is stored at `val[base[r] + c]` with `check[base[r] + c] == r + 1` if it is not `default`
+// most entries of DFA_EDGE, ACTION and GOTO are default (dead state or error), so the rows can fill the gaps between each other
+fn comb(table: Vec<Vec<u32>>, default: u32) -> String {
+  let (rows, cols) = (table.len(), table.first().map_or(0, Vec::len));
+  // place the dense rows first, the sparse rows are easier to fit in the remaining gaps
+  let mut order = (0..rows).collect::<Vec<_>>();
+  order.sort_by_key(|&r| std::cmp::Reverse(table[r].iter().filter(|&&x| x != default).count()));
+  let (mut base, mut check, mut val) = (vec![0; rows], Vec::new(), Vec::new());
+  for r in order {
+    let b = (0..).find(|&b| table[r].iter().enumerate()
+      .all(|(c, &x)| x == default || check.get(b + c).map_or(true, |&k| k == 0))).unwrap();
+    // every `base[r] + c` is in bounds
+    if check.len() < b + cols { check.resize(b + cols, 0); val.resize(b + cols, default); }
+    for (c, &x) in table[r].iter().enumerate() {
+      if x != default { check[b + c] = r + 1; val[b + c] = x; }
+    }
+    base[r] = b;
+  }
+  let arr = |v: &[usize]| format!("new {}([{}])", min_u_arr(v.iter().max().map_or(0, |&x| x + 1)), fmt::comma_sep(v.iter()));
+  let val = val.iter().map(|&x| x as usize).collect::<Vec<_>>();
+  format!("new Comb({}, {}, {}, {})", arr(&base), arr(&check), arr(&val), default)
+}
+
+impl<W: std::io::Write> Config<'_, W> {
+  // the code to run when ACTION has no entry for the token, like `fmt::lr_error`, but ACTION is flattened
+  fn ts_error<'a>(g: &'a Grammar) -> impl Display + 'a {
+    fmt_::fn2display(move |f| {
+      let name = |t: u32| g.terms[t as usize].name;
+      if !g.fallback.is_empty() {
+        f.write_str("switch(token.kind){")?;
+        for (from, to) in &g.fallback {
+          write!(f, "case TokenKind.{}:", name(*from))?;
+          for &x in to {
+            write!(f, "if((ACTION.get(state,TokenKind.{0})&3)!==3){{token.kind=TokenKind.{0};continue;}}", name(x))?;
+          }
+          f.write_str("break;")?;
+        }
+        f.write_str("default:break;}")?;
+      }
+      f.write_str("return{ok:false,token};")
+    })
+  }
+
+  fn ts_act<'a>(g: &'a Grammar) -> impl Display + 'a {
+    fmt_::fn2display(move |f| {
+      for (i, prod) in g.prod.iter().enumerate() {
+        write!(f, "case {}:{{", i)?;
+        for (j, &x) in prod.rhs.iter().enumerate().rev() {
+          let name = fmt_::fn2display(move |f|
+            match prod.args { Some(args) => f.write_str(args[j].0), None => write!(f, "_{}", j + 1) });
+          let ty = if let Some(x) = g.as_nt(x) { g.nt[x].ty } else { "Token" };
+          writeln!(f, "const {}=stk.pop()!.value as {};", name, ty)?;
+        }
+        writeln!(f, "let __!:{};{}value=__;break;}}", g.nt[prod.lhs as usize].ty,
+          if i == g.prod.len() - 1 { "__=_1;".into() } else { fmt::act(g, prod, Lang::TypeScript) })?;
+      }
+      Ok(())
+    })
+  }
+
+  pub fn ts_lalr1(&mut self, g: &Grammar, table: &Table, dfa: &Dfa) -> Result<()> {
+    let (types, _) = fmt::gather_types(g);
+    let res_type = g.nt.last().unwrap().ty;
+    write!(
+      self.code_output, include_str!("template/lalr1.ts.template"),
+      include = g.raw.include,
+      token_kind = fmt::comma_sep(g.terms.iter().map(|x| x.name)),
+      u_term_arr = min_u_arr(g.terms.len()),
+      acc = fmt::acc(g, dfa, "TokenKind."),
+      ec = fmt::comma_sep(dfa.ec.iter()),
+      dfa_edge = comb(fmt::dfa_edge_rows(dfa), 0),
+      lexer_field = g.raw.lexer_field,
+      lexer_action = g.raw.lexer_action,
+      col_step = fmt::col_step(g, Lang::TypeScript),
+      value_type = fmt_::sep(std::iter::once("Token".to_owned()).chain(types.iter().map(|ty| format!("({})", ty))), " | "),
+      res_type = res_type,
+      u_nt_arr = min_u_arr(g.nt.len()),
+      prod = fmt::comma_sep(g.prod.iter().map(|x| x.lhs)),
+      action = comb(fmt::action_rows(g, table), 3),
+      goto = comb(fmt::goto_rows(g, table), 0),
+      parser_type = g.raw.parser_def.unwrap_or("Parser"),
+      parser_field = g.raw.parser_field,
+      parser_act = Self::ts_act(g),
+      on_error = Self::ts_error(g),
+      epilogue = g.raw.epilogue.unwrap_or(""),
+    )
+  }
+}
@@ -0,0 +1,120 @@
+// expand unicode classes `\p{..}` / `\P{..}` in regex to the alternation of their utf-8 byte sequences
+// re2dfa works on bytes, so the result is not necessarily valid utf-8
+use regex_syntax::{hir::{Class, HirKind}, utf8::Utf8Sequences};
+use std::borrow::Cow;
+
+// write a byte range as a char class, ascii chars that are special in a char class are escaped
+fn byte_class(lo: u8, hi: u8, out: &mut Vec<u8>) {
+  fn byte(b: u8, out: &mut Vec<u8>) {
+    if b"\\]^-[".contains(&b) { out.push(b'\\'); }
+    out.push(b);
+  }
+  out.push(b'[');
+  byte(lo, out);
+  if lo != hi {
+    out.push(b'-');
+    byte(hi, out);
+  }
+  out.push(b']');
+}
+
+// `re` is a regex of a char class, e.g., `\p{L}`, `.` or `[^a-z]`, which is expanded to the alternation of utf-8 byte sequences
+fn class(re: &str, out: &mut Vec<u8>) -> Result<(), String> {
+  let hir = regex_syntax::Parser::new().parse(re).map_err(|e| format!("invalid unicode class {}: {}", re, e))?;
+  let cls = match hir.kind() {
+    HirKind::Class(Class::Unicode(cls)) => cls,
+    _ => return Err(format!("{} is not a unicode class", re)),
+  };
+  out.push(b'(');
+  let mut first = true;
+  for r in cls.ranges() {
+    for seq in Utf8Sequences::new(r.start(), r.end()) {
+      if !first { out.push(b'|'); }
+      first = false;
+      for r in seq.as_slice() { byte_class(r.start, r.end, out); }
+    }
+  }
+  out.push(b')');
+  Ok(())
+}
+
+// the end of the `[]` starting at `i`, a `]` right after `[` or `[^` is a literal
+fn bracket_end(bytes: &[u8], i: usize) -> Option<usize> {
+  let mut j = i + 1;
+  if bytes.get(j) == Some(&b'^') { j += 1; }
+  if bytes.get(j) == Some(&b']') { j += 1; }
+  let mut depth = 1;
+  while j < bytes.len() {
+    match bytes[j] {
+      b'\\' => j += 1,
+      b'[' => depth += 1,
+      b']' => {
+        depth -= 1;
+        if depth == 0 { return Some(j + 1); }
+      }
+      _ => {}
+    }
+    j += 1;
+  }
+  None
+}
+
+// in `unicode` mode, `.` and `[^...]` match a code point instead of a byte
+pub fn expand(re: &str, unicode: bool) -> Result<Cow<[u8]>, String> {
+  if !re.contains("\\p") && !re.contains("\\P") && re.is_ascii() && !(unicode && (re.contains('.') || re.contains("[^"))) {
+    return Ok(Cow::Borrowed(re.as_bytes()));
+  }
+  let (bytes, mut out, mut i, mut in_class) = (re.as_bytes(), Vec::with_capacity(re.len()), 0, false);
+  while i < bytes.len() {
+    match bytes[i] {
+      b'\\' if matches!(bytes.get(i + 1), Some(b'p') | Some(b'P')) => {
+        if in_class { return Err("unicode class inside [] is not supported, use alternation like (\\p{L}|[_]) instead".to_owned()); }
+        let end = match bytes.get(i + 2) {
+          Some(b'{') => re[i..].find('}').map(|x| i + x + 1),
+          Some(_) => Some(i + 3), // one letter name, e.g., \pL
+          None => None,
+        }.ok_or_else(|| format!("unterminated unicode class at {}", i))?;
+        class(&re[i..end], &mut out)?;
+        i = end;
+        continue;
+      }
+      // an escaped non-ascii char is the char itself, handled below
+      b'\\' if !in_class && bytes.get(i + 1).map_or(false, |&b| b >= 0x80) => {
+        i += 1;
+        continue;
+      }
+      b'\\' => {
+        out.extend_from_slice(&bytes[i..(i + 2).min(bytes.len())]);
+        i += 2;
+        continue;
+      }
+      b'.' if unicode && !in_class => {
+        class(".", &mut out)?;
+        i += 1;
+        continue;
+      }
+      b'[' if unicode && !in_class && bytes.get(i + 1) == Some(&b'^') => {
+        let end = bracket_end(bytes, i).ok_or_else(|| format!("unterminated [] at {}", i))?;
+        class(&re[i..end], &mut out)?;
+        i = end;
+        continue;
+      }
+      b'[' if !in_class => in_class = true,
+      b']' if in_class => in_class = false,
+      ch if ch >= 0x80 && in_class => return Err("non-ascii char inside [] is not supported, use alternation instead".to_owned()),
+      // group the bytes of a non-ascii char, so that a following quantifier applies to the whole char
+      ch if ch >= 0x80 => {
+        let len = re[i..].chars().next().map_or(1, char::len_utf8);
+        out.push(b'(');
+        out.extend_from_slice(&bytes[i..i + len]);
+        out.push(b')');
+        i += len;
+        continue;
+      }
+      _ => {}
+    }
+    out.push(bytes[i]);
+    i += 1;
+  }
+  Ok(Cow::Owned(out))
+}
@@ -0,0 +1,282 @@
+// #[derive(Lalr1)]: build the grammar from an enum (or a struct), each variant is a production of the non-terminal named by the type,
+// whose action constructs the variant from the values of rhs symbols
+// #[lalr1(Start)] on a module does the same for all the enums and structs with #[rule] in it, so they can refer to each other
+use crate::*;
+use quote::quote;
+use std::borrow::Cow;
+use syn::{Data, DeriveInput, GenericParam, ItemMod, Lit, Meta};
+
+// the attributes read by `Config`, other attributes (e.g., doc comments) on the type are ignored
+const CONFIG: &[&str] = &["lex", "lex_path", "verbose", "show_fsm", "show_dfa", "log_token", "log_reduce", "use_unsafe",
+  "recursive_descent", "direct_lr", "direct_lexer", "expand", "deny_conflicts"];
+
+// a symbol in the pattern of `#[rule]`
+enum Pat<'a> {
+  // `{0}`, `{name}`, or `{0:Symbol}`, the value of the field is the value of the symbol
+  // the symbol can be omitted if the field is a derived type (maybe boxed)
+  // `{0:Symbol*}` and `{0:Symbol+}` are lists of the symbol, the field should be a `Vec`
+  Field(&'a str, Option<&'a str>),
+  // `'+'`, the term whose lexical rule is this literal, a new rule is added before all lexical rules if not found
+  Lit(&'a str),
+  // a term or non-terminal, its value is discarded
+  Sym(&'a str),
+}
+
+fn parse_pat(mut s: &str) -> Option<Vec<Pat<'_>>> {
+  let mut ret = Vec::new();
+  s = s.trim_start();
+  while !s.is_empty() {
+    let end = if s.starts_with('\'') {
+      let end = s[1..].find('\'')? + 2;
+      ret.push(Pat::Lit(&s[1..end - 1]));
+      end
+    } else if s.starts_with('{') {
+      let end = s.find('}')? + 1;
+      let field = &s[1..end - 1];
+      ret.push(match field.find(':') {
+        Some(i) => Pat::Field(field[..i].trim(), Some(field[i + 1..].trim())),
+        None => Pat::Field(field.trim(), None),
+      });
+      end
+    } else {
+      let end = s.find(char::is_whitespace).unwrap_or(s.len());
+      ret.push(Pat::Sym(&s[..end]));
+      end
+    };
+    s = s[end..].trim_start();
+  }
+  Some(ret)
+}
+
+// `#[rule("pattern")]` or `#[rule("pattern", prec = "Term")]`
+fn parse_rule(attr: &Attribute) -> Result<(String, Option<String>)> {
+  let err = || Error::new_spanned(attr, "expect #[rule(\"pattern\")] or #[rule(\"pattern\", prec = \"Term\")]");
+  let list = match attr.parse_meta()? { Meta::List(x) => x, _ => return Err(err()) };
+  let mut it = list.nested.iter();
+  let pat = match it.next() { Some(NestedMeta::Lit(Lit::Str(x))) => x.value(), _ => return Err(err()) };
+  let prec = match it.next() {
+    None => None,
+    Some(NestedMeta::Meta(Meta::NameValue(nv))) if nv.path.is_ident("prec") =>
+      match &nv.lit { Lit::Str(x) => Some(x.value()), _ => return Err(err()) },
+    _ => return Err(err()),
+  };
+  if it.next().is_some() { return Err(err()); }
+  Ok((pat, prec))
+}
+
+// the derived types, i.e., the non-terminals, in the form of (name, type with generics)
+type Nts<'a> = [(&'a str, &'a str)];
+
+// Some((name, boxed)) if `ty` is a derived type (`Self` is `this`), or `Box` of it
+fn derived<'a>(ty: &Type, this: &'a str, nts: &Nts<'a>) -> Option<(&'a str, bool)> {
+  let seg = match ty { Type::Path(p) => p.path.segments.last()?, _ => return None };
+  if seg.ident == "Self" { return Some((this, false)); }
+  if let Some(&(name, _)) = nts.iter().find(|x| seg.ident == x.0) { return Some((name, false)); }
+  if seg.ident != "Box" { return None; }
+  match &seg.arguments {
+    PathArguments::AngleBracketed(args) => match args.args.first() {
+      Some(GenericArgument::Type(t)) if args.args.len() == 1 => derived(t, this, nts).filter(|x| !x.1).map(|x| (x.0, true)),
+      _ => None,
+    }
+    _ => None,
+  }
+}
+
+// regex meta characters
+fn escape(lit: &str) -> String {
+  let mut s = String::with_capacity(lit.len());
+  for ch in lit.chars() {
+    if "\\.+*?()|[]{}^$".contains(ch) { s.push('\\'); }
+    s.push(ch);
+  }
+  s
+}
+
+// the terms of literals in patterns
+struct Literals<'a> {
+  // lexical rules added for the literals not found in lex, they are put before all lexical rules, so keywords are not lexed as identifiers
+  added: Vec<(Cow<'a, str>, &'a str)>,
+  arena: &'a Arena<u8>,
+}
+
+impl<'a> Literals<'a> {
+  fn term(&mut self, lex: &RawLexer<'a>, lit: &str) -> &'a str {
+    let re = escape(lit);
+    let found = lex.lexical.iter().map(|(k, &v)| (k, v)).chain(self.added.iter().map(|(k, v)| (k, *v)))
+      .find(|(k, _)| **k == re || **k == lit).map(|(_, v)| v);
+    if let Some(term) = found { return term; }
+    let used = |name: &str| lex.lexical.values().chain(self.added.iter().map(|(_, v)| v)).any(|&x| x == name)
+      || lex.priority.iter().any(|row| row.terms.contains(&name));
+    let name = (0..).map(|i| format!("Lit{}", i)).find(|x| !used(x)).unwrap();
+    let name = &*self.arena.alloc_str(&name);
+    self.added.push((Cow::Owned(re), name));
+    name
+  }
+}
+
+// the name and type (with generics) of a derived type
+fn nt_of<'a>(input: &DeriveInput, arena: &'a Arena<u8>) -> Result<(&'a str, &'a str)> {
+  let ident = &input.ident;
+  for p in &input.generics.params {
+    match p {
+      GenericParam::Lifetime(l) if l.lifetime.ident == "p" => {}
+      _ => return Err(Error::new_spanned(p, "#[derive(Lalr1)] only supports lifetime `'p`, which is the lifetime of `Token<'p>`")),
+    }
+  }
+  let (_, ty_generics, _) = input.generics.split_for_impl();
+  Ok((arena.alloc_str(&ident.to_string()), arena.alloc_str(&quote!(#ident #ty_generics).to_string())))
+}
+
+// the productions of all derived types, and the lists used by `{0:Symbol*}` and `{0:Symbol+}`
+fn productions<'a>(inputs: &[DeriveInput], nts: &Nts<'a>, lex: &mut RawLexer<'a>, arena: &'a Arena<u8>) -> Result<(Vec<RawProduction<'a>>, Vec<RuleSpan<'a>>)> {
+  let mut lits = Literals { added: Vec::new(), arena };
+  let mut production = Vec::new();
+  let mut lists = Vec::<RawProduction>::new();
+  let mut rules = Vec::new();
+  for (input, &(name, nt_ty)) in inputs.iter().zip(nts) {
+    let ident = &input.ident;
+    // (the path to construct the variant, its fields, its attributes, the span to report missing rules)
+    let variants: Vec<_> = match &input.data {
+      Data::Enum(e) => e.variants.iter().map(|v| (format!("{}::{}", name, v.ident), &v.fields, &v.attrs, v.ident.span())).collect(),
+      Data::Struct(s) => vec![(name.to_owned(), &s.fields, &input.attrs, ident.span())],
+      Data::Union(_) => return Err(Error::new_spanned(ident, "#[derive(Lalr1)] doesn't support union")),
+    };
+    let mut rhs_list = Vec::new();
+    for (path, fields, attrs, span) in variants {
+      let rule_attrs = attrs.iter().filter(|x| x.path.is_ident("rule")).collect::<Vec<_>>();
+      if rule_attrs.is_empty() { return Err(Error::new(span, format!("`{}` has no #[rule]", path))); }
+      let fields = fields.iter().enumerate().map(|(i, f)| (f.ident.as_ref().map(|x| x.to_string()).unwrap_or_else(|| i.to_string()), f)).collect::<Vec<_>>();
+      for attr in rule_attrs {
+        let rule_span = attr.span();
+        let err = |msg: String| Error::new(rule_span, msg);
+        let (pat, prec) = parse_rule(attr)?;
+        let pat = parse_pat(arena.alloc_str(&pat)).ok_or_else(|| err(format!("unclosed `'` or `{{` in rule of `{}`", path)))?;
+        let sym_ty = |lex: &RawLexer<'a>, t: &str| match nts.iter().find(|x| x.0 == t) {
+          Some(x) => x.1,
+          None => lex.term_value.get(t).map(|x| x.ty).unwrap_or("Token<'p>"),
+        };
+        let (mut rhs, mut rhs_arg) = (Vec::new(), Vec::new());
+        // the expression of each field
+        let mut value = vec![None; fields.len()];
+        for p in pat {
+          match p {
+            Pat::Field(f, sym) => {
+              let idx = fields.iter().position(|x| x.0 == f).ok_or_else(|| err(format!("`{}` has no field `{}`", path, f)))?;
+              if value[idx].is_some() { return Err(err(format!("field `{}` of `{}` appears more than once in rule", f, path))); }
+              let ty = &fields[idx].1.ty;
+              let nt = derived(ty, name, nts);
+              let sym = match (sym, nt) {
+                (Some(sym), _) => sym,
+                (None, Some((nt, _))) => nt,
+                (None, None) => return Err(err(format!("the symbol of field `{}` of `{}` can't be inferred, write it as `{{{}:Symbol}}`", f, path, f))),
+              };
+              let var = &*arena.alloc_str(&format!("_{}", idx));
+              let boxed = nt.map_or(false, |x| x.0 == sym && x.1);
+              let sym = if let Some(elem) = sym.strip_suffix('*').or_else(|| sym.strip_suffix('+')) {
+                // `Symbol_list0 -> | Symbol_list0 Symbol`, or `Symbol_list1 -> Symbol | Symbol_list1 Symbol`
+                let (list, empty) = if sym.ends_with('*') { (format!("{}_list0", elem), true) } else { (format!("{}_list1", elem), false) };
+                let list = &*arena.alloc_str(&list);
+                if !lists.iter().any(|x| x.lhs == list) {
+                  let list_ty = &*arena.alloc_str(&format!("Vec<{}>", sym_ty(lex, elem)));
+                  let first = if empty {
+                    RawProductionRhs { rhs: vec![], rhs_arg: Some(vec![]), act: "{Vec::new()}", prec: None, fallible: false }
+                  } else {
+                    RawProductionRhs { rhs: vec![elem], rhs_arg: Some(vec![("x", sym_ty(lex, elem))]), act: "{vec![x]}", prec: None, fallible: false }
+                  };
+                  let rest = RawProductionRhs { rhs: vec![list, elem], rhs_arg: Some(vec![("mut l", list_ty), ("x", sym_ty(lex, elem))]),
+                    act: "{l.push(x);l}", prec: None, fallible: false };
+                  lists.push(RawProduction { lhs: list, ty: list_ty, rhs: vec![first, rest] });
+                }
+                list
+              } else { sym };
+              let arg_ty = match nts.iter().find(|x| x.0 == sym) {
+                Some(x) => x.1,
+                None => match lists.iter().find(|x| x.lhs == sym) { Some(x) => x.ty, None => &*arena.alloc_str(&ty.to_token_stream().to_string()) },
+              };
+              value[idx] = Some(if boxed { format!("Box::new({})", var) } else { var.to_owned() });
+              rhs.push(sym);
+              rhs_arg.push((var, arg_ty));
+            }
+            Pat::Lit(lit) => {
+              if lit.is_empty() { return Err(err(format!("empty literal in rule of `{}`", path))); }
+              let term = lits.term(lex, lit);
+              rhs.push(term);
+              rhs_arg.push(("_", sym_ty(lex, term)));
+            }
+            Pat::Sym(sym) => {
+              rhs.push(sym);
+              rhs_arg.push(("_", sym_ty(lex, sym)));
+            }
+          }
+        }
+        if let Some(idx) = value.iter().position(Option::is_none) {
+          return Err(err(format!("field `{}` of `{}` is not in rule", fields[idx].0, path)));
+        }
+        let value = value.into_iter().map(Option::unwrap).collect::<Vec<_>>();
+        let act = match fields.first().and_then(|x| x.1.ident.as_ref()) {
+          _ if fields.is_empty() => format!("{{{}}}", path),
+          Some(_) => format!("{{{}{{{}}}}}", path, fields.iter().zip(&value).map(|((f, _), v)| format!("{}:{}", f, v)).collect::<Vec<_>>().join(",")),
+          None => format!("{{{}({})}}", path, value.join(",")),
+        };
+        let prec = match prec {
+          Some(p) if p.starts_with('\'') && p.ends_with('\'') && p.len() > 2 => Some(lits.term(lex, &p[1..p.len() - 1])),
+          Some(p) => Some(&*arena.alloc_str(&p)),
+          None => None,
+        };
+        rules.push(RuleSpan { lhs: name, rhs: rhs.clone(), prec, rule_span, prec_span: rule_span });
+        rhs_list.push(RawProductionRhs { rhs, rhs_arg: Some(rhs_arg), act: arena.alloc_str(&act), prec, fallible: false });
+      }
+    }
+    production.push(RawProduction { lhs: name, ty: nt_ty, rhs: rhs_list });
+  }
+  if !lits.added.is_empty() {
+    let old = std::mem::take(&mut lex.lexical);
+    lex.lexical = lits.added.into_iter().chain(old).collect();
+  }
+  production.extend(lists);
+  Ok((production, rules))
+}
+
+pub fn work(input: DeriveInput) -> Result<TokenStream2> {
+  let arena = Arena::new();
+  let nt = nt_of(&input, &arena)?;
+  let attrs = input.attrs.iter().filter(|x| CONFIG.iter().any(|&n| x.path.is_ident(n))).cloned().collect::<Vec<_>>();
+  let cfg = Config::from_list(&parse_attrs(&attrs)?).map_err(|e| Error::new_spanned(&input.ident, format!("failed to read attributes: {}", e)))?;
+  let (lex, lex_span) = read_lex(&input.attrs, &cfg)?;
+  let (mut lex, tokens) = parse_lex(lex.as_deref(), lex_span, &input.attrs, &arena)?;
+  let (production, rules) = productions(std::slice::from_ref(&input), &[nt], &mut lex, &arena)?;
+  generate(&cfg, grammar(lex, nt.0, production, None), PGAlgo::LALR1, &rules, &tokens, lex_span)
+}
+
+// the `#[rule]`s are removed from the items, and the parser is put at the end of the module
+pub fn work_mod(start: &str, mut m: ItemMod, algo: PGAlgo) -> Result<TokenStream2> {
+  let arena = Arena::new();
+  let attrs = m.attrs.iter().filter(|x| !x.path.is_ident("token")).cloned().collect::<Vec<_>>();
+  let cfg = Config::from_list(&parse_attrs(&attrs)?).map_err(|e| Error::new_spanned(&m.ident, format!("failed to read attributes: {}", e)))?;
+  let (lex, lex_span) = read_lex(&m.attrs, &cfg)?;
+  let (mut lex, tokens) = parse_lex(lex.as_deref(), lex_span, &m.attrs, &arena)?;
+  let items = match &mut m.content {
+    Some((_, items)) => items,
+    None => return Err(Error::new_spanned(&m, "the grammar can only be built from an inline module")),
+  };
+  let has_rule = |attrs: &[Attribute]| attrs.iter().any(|x| x.path.is_ident("rule"));
+  let inputs = items.iter().filter_map(|x| match x {
+    Item::Enum(e) if e.variants.iter().any(|v| has_rule(&v.attrs)) => Some(DeriveInput::from(e.clone())),
+    Item::Struct(s) if has_rule(&s.attrs) => Some(DeriveInput::from(s.clone())),
+    _ => None,
+  }).collect::<Vec<_>>();
+  let nts = inputs.iter().map(|x| nt_of(x, &arena)).collect::<Result<Vec<_>>>()?;
+  if !nts.iter().any(|x| x.0 == start) { return Err(Error::new_spanned(&m.ident, format!("no enum or struct `{}` with #[rule] in module", start))); }
+  let (production, rules) = productions(&inputs, &nts, &mut lex, &arena)?;
+  let code = generate(&cfg, grammar(lex, arena.alloc_str(start), production, None), algo, &rules, &tokens, lex_span)?;
+  for item in items.iter_mut() {
+    match item {
+      Item::Enum(e) => for v in &mut e.variants { v.attrs.retain(|x| !x.path.is_ident("rule")); }
+      Item::Struct(s) => s.attrs.retain(|x| !x.path.is_ident("rule")),
+      _ => {}
+    }
+  }
+  items.push(Item::Verbatim(code));
+  m.attrs.retain(|x| !x.path.is_ident("token") && !CONFIG.iter().any(|&n| x.path.is_ident(n)));
+  Ok(m.into_token_stream())
+}
@@ -1,13 +1,20 @@
-#![feature(proc_macro_diagnostic)]
 extern crate proc_macro;
 
-use quote::ToTokens;
-use proc_macro::{Diagnostic, Level, TokenStream};
-use syn::{FnArg, NestedMeta, ItemImpl, ImplItem, Attribute, ReturnType, Error};
+use quote::{ToTokens, quote_spanned};
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree};
+use std::{fmt::Write, io};
+use syn::{spanned::Spanned, FnArg, NestedMeta, Item, ImplItem, ImplItemMethod, Attribute, ReturnType, Error, Result, Type, PathArguments, GenericArgument, Meta, MetaNameValue, Lit};
 use darling::FromMeta;
 use typed_arena::Arena;
 use parser_gen::*;
 use common::*;
+use lalr1_core::{Conflict, Lr1Fsm, Table};
+use ll1_core::LLCtx;
+use re2dfa::Dfa;
+
+mod derive;
+mod token;
 
 fn parse_arg(arg: &FnArg) -> Option<(String, String)> {
   match arg {
@@ -26,105 +33,318 @@ struct Config {
   #[darling(default)] log_token: bool,
   #[darling(default)] log_reduce: bool,
   #[darling(default)] use_unsafe: bool,
+  #[darling(default)] recursive_descent: bool,
+  #[darling(default)] direct_lr: bool,
+  #[darling(default)] direct_lexer: bool,
   #[darling(default)] expand: bool,
+  // report conflicts as errors instead of warnings
+  #[darling(default)] deny_conflicts: bool,
 }
 
-// part of RawGrammar
-#[derive(serde::Deserialize)]
-struct RawLexer<'a> {
-  #[serde(borrow)]
-  priority: Vec<RawPriorityRow<'a>>,
-  lexical: IndexMap<std::borrow::Cow<'a, str>, &'a str>,
-  #[serde(default)] lexer_field: &'a str,
-  #[serde(default)] lexer_action: &'a str,
+// return (T, E) if `ty` is `Result<T, E>`
+fn split_result(ty: &Type) -> Option<(&Type, &Type)> {
+  let seg = match ty { Type::Path(p) => p.path.segments.last()?, _ => return None };
+  if seg.ident != "Result" { return None; }
+  match &seg.arguments {
+    PathArguments::AngleBracketed(args) => match (args.args.len(), args.args.first(), args.args.last()) {
+      (2, Some(GenericArgument::Type(t)), Some(GenericArgument::Type(e))) => Some((t, e)),
+      _ => None,
+    }
+    _ => None,
+  }
 }
 
-#[derive(FromMeta)]
-struct Rule {
-  rule: String,
-  #[darling(default)] prec: Option<String>,
+fn no_space(s: &str) -> String { s.chars().filter(|x| !x.is_whitespace()).collect() }
+
+// the `#[rule]`s of a method, each followed by an optional `#[prec]`, in the form of (rule, prec, rule span, prec span)
+fn method_rules(method: &ImplItemMethod) -> Result<Vec<(String, Option<String>, Span, Span)>> {
+  let mut ret = Vec::<(String, Option<String>, Span, Span)>::new();
+  for attr in &method.attrs {
+    let (is_rule, is_prec) = (attr.path.is_ident("rule"), attr.path.is_ident("prec"));
+    if !is_rule && !is_prec { continue; }
+    let value = match attr.parse_meta()? {
+      Meta::NameValue(MetaNameValue { lit: Lit::Str(s), .. }) => s.value(),
+      _ => return Err(Error::new(attr.span(), if is_rule { "expect #[rule = \"lhs -> rhs1 rhs2 ...\"]" } else { "expect #[prec = \"Term\"]" })),
+    };
+    if is_rule { ret.push((value, None, attr.span(), attr.span())); } else {
+      match ret.last_mut() {
+        Some(last) if last.1.is_none() => { last.1 = Some(value); last.3 = attr.span(); }
+        _ => return Err(Error::new(attr.span(), format!("#[prec] of method `{}` should follow a #[rule]", method.sig.ident))),
+      }
+    }
+  }
+  if ret.is_empty() { return Err(Error::new(method.sig.ident.span(), format!("method `{}` has no #[rule]", method.sig.ident))); }
+  Ok(ret)
+}
+
+// whether `self` appears in `ts`
+fn uses_self(ts: TokenStream2) -> bool {
+  ts.into_iter().any(|t| match t {
+    TokenTree::Ident(i) => i == "self",
+    TokenTree::Group(g) => uses_self(g.stream()),
+    _ => false,
+  })
+}
+
+fn parse_attrs(attrs: &[Attribute]) -> Result<Vec<NestedMeta>> {
+  attrs.iter().map(|x| x.parse_meta().map(NestedMeta::Meta)).collect()
 }
 
-struct E(Error); // pretty print `Error` with location info
+fn attr_span(attrs: &[Attribute], name: &str) -> Option<Span> {
+  attrs.iter().find(|x| x.path.is_ident(name)).map(|x| x.span())
+}
+
+// forward everything to `parser_gen::Config`, but collect the conflicts and lexical warnings
+// proc_macro::Diagnostic is unstable, so they are reported by `work` in other ways
+// the errors are located when they are created, `Span` is not `Send`, so it can't be in the `io::Error` returned by `work`
+struct Collect<'a, 'b> {
+  cfg: parser_gen::Config<'a, Vec<u8>>,
+  conflicts: Vec<String>,
+  warnings: Vec<String>,
+  rules: &'b [RuleSpan<'b>],
+  tokens: &'b [token::TokenSpan],
+  lex_span: Span,
+  error: Option<Error>,
+}
 
-impl Debug for E {
-  fn fmt(&self, f: &mut Formatter) -> FmtResult {
-    let lc = self.0.span().start();
-    write!(f, "{} at {}:{}", self.0, lc.line, lc.column)
+impl Collect<'_, '_> {
+  // `re` is the invalid regex, which is located at its `#[token]`
+  // `lex` is true if it is about the lexer, which is located at the `lex`/`lex_path` attribute if no method or `#[token]` is related
+  fn fail(&mut self, msg: String, lex: bool, re: Option<&str>) -> io::Error {
+    let span = match re {
+      Some(re) => self.tokens.iter().find(|t| t.re.as_deref() == Some(re)).map(|t| t.span),
+      None => locate(&msg, self.rules).or_else(|| token::locate(&msg, self.tokens)),
+    };
+    self.error = Some(Error::new(span.unwrap_or(if lex { self.lex_span } else { Span::call_site() }), &msg));
+    io::Error::new(io::ErrorKind::InvalidData, msg)
   }
 }
 
-fn parse_attrs(attrs: &[Attribute]) -> Vec<NestedMeta> {
-  attrs.iter().map(|x| NestedMeta::Meta(x.parse_meta().map_err(E).expect("failed to parse meta"))).collect()
+impl Codegen for Collect<'_, '_> {
+  fn grammar_error(&mut self, reason: String) -> io::Error { self.fail(format!("invalid grammar, reason: {}", reason), false, None) }
+
+  fn re2dfa_error(&mut self, re: &str, reason: String) -> io::Error {
+    self.fail(format!("invalid regex {}, reason: {}", re, reason), true, Some(re))
+  }
+
+  fn lexical_warning(&mut self, reason: String) { self.warnings.push(reason); }
+
+  // `Config` reports its own errors, which are located at the call site, except the error about the dfa, which is about the lexer
+  fn dfa(&mut self, dfa: &Dfa) -> io::Result<()> {
+    self.cfg.dfa(dfa).map_err(|e| self.fail(e.to_string(), true, None))
+  }
+
+  fn ll(&mut self, g: &Grammar, ll: LLCtx, dfa: &Dfa) -> io::Result<()> {
+    self.conflicts.extend(show_ll::conflict(&ll.table, g));
+    self.cfg.ll(g, ll, dfa)
+  }
+
+  fn lr1(&mut self, g: &Grammar, lr1: &Lr1Fsm, dfa: &Dfa, orig_table: Table, table: Table, conflict: Vec<Conflict>) -> io::Result<()> {
+    self.conflicts.extend(show_lr::conflict(g, &conflict));
+    self.cfg.lr1(g, lr1, dfa, orig_table, table, conflict)
+  }
+
+  fn lang(&self) -> Lang { self.cfg.lang }
 }
 
-fn work(attr: TokenStream, input: TokenStream, algo: PGAlgo) -> TokenStream {
-  let parser = syn::parse::<ItemImpl>(input).map_err(E).expect("failed to parse impl block");
-  let start = &attr.to_string();
-  let parser_def = parser.self_ty.to_token_stream().to_string();
+// the `#[rule]` and `#[prec]` of a method, used to locate the messages from parser_gen
+struct RuleSpan<'a> { lhs: &'a str, rhs: Vec<&'a str>, prec: Option<&'a str>, rule_span: Span, prec_span: Span }
 
-  let Config { lex, lex_path, verbose, show_fsm, show_dfa, log_token, log_reduce, use_unsafe, expand }
-    = Config::from_list(&parse_attrs(&parser.attrs)).expect("failed to read attributes");
-  let lex = if let Some(lex) = lex { lex } else {
-    std::fs::read_to_string(lex_path.expect("attributes must contain `lex` or `lex_path`")).expect("failed to read lex")
-  };
-  let mut cfg = parser_gen::Config {
-    verbose: verbose.as_deref(),
-    show_fsm: show_fsm.as_deref(),
-    show_dfa: show_dfa.as_deref(),
-    log_token,
-    log_reduce,
-    use_unsafe,
-    lang: Lang::Rs,
-    on_conflict: |c| Diagnostic::new(Level::Warning, c).emit(),
-    code_output: Vec::new(),
-  };
-  let lex = toml::from_str::<RawLexer>(&lex).expect("failed to parse lexer toml");
+// parser_gen quotes the productions and symbols in its messages, e.g., "production rhs contains undefined token: \"Foo\""
+// locate the message at the rule containing the first quoted production, or else the first quoted symbol
+fn locate(msg: &str, rules: &[RuleSpan]) -> Option<Span> {
+  let quoted = msg.split('"').skip(1).step_by(2).map(no_space).collect::<Vec<_>>();
+  quoted.iter().find_map(|q| rules.iter().find(|r| *q == format!("{}->{}", r.lhs, r.rhs.concat())).map(|r| r.rule_span))
+    .or_else(|| quoted.iter().find_map(|q| rules.iter().find_map(|r| {
+      if r.prec == Some(q.as_str()) { Some(r.prec_span) } else if r.lhs == q.as_str() || r.rhs.contains(&q.as_str()) { Some(r.rule_span) } else { None }
+    })))
+}
 
-  let mut production = Vec::new();
-  let arena = Arena::new();
-  for item in &parser.items {
-    if let ImplItem::Method(method) = item {
-      let Rule { rule, prec } = Rule::from_list(&parse_attrs(&method.attrs)).expect("failed to parse rule");
-      let rule = &*arena.alloc_str(&rule);
-      let prec = prec.map(|x| &*arena.alloc_str(&x));
-      let (lhs, rhs) = parse_arrow_prod(&rule).unwrap_or_else(||
-        panic!("rule \"{}\" of method `{}` is not in the form of \"lhs -> rhs1 rhs2 ...\"", rule, method.sig.ident));
-      let ty = match &method.sig.output {
-        ReturnType::Default => "()",
-        ReturnType::Type(_, ty) => arena.alloc_str(&ty.to_token_stream().to_string()),
-      };
-      let rhs_arg = method.sig.inputs.iter().map(parse_arg).collect::<Vec<_>>();
-      let skip_self = match rhs_arg.get(0) { Some(None) => 1, _ => 0, };
-      let rhs_arg = Some(rhs_arg.into_iter().skip(skip_self).map(|arg| match arg {
-        None => panic!("method `{}` takes `self` at illegal position", method.sig.ident),
-        Some((pat, name)) => (&*arena.alloc_str(&pat), &*arena.alloc_str(&name)),
-      }).collect());
-      let act = arena.alloc_str(&method.block.to_token_stream().to_string());
-      production.push(RawProduction { lhs, ty, rhs: vec![RawProductionRhs { rhs, rhs_arg, act, prec }] });
-    } else { panic!("only support method impl, found {:?}", item); }
+// a deprecated item is the only way to emit a custom warning on stable
+fn warning(msg: &str, span: Span) -> TokenStream2 {
+  quote_spanned! { span =>
+    const _: () = {
+      #[deprecated(note = #msg)]
+      #[allow(non_camel_case_types)]
+      struct parser_warning;
+      let _ = parser_warning;
+    };
   }
+}
 
-  let g = RawGrammar {
+// read the lexer toml in `lex`, or in the file at `lex_path`, it can be omitted if there are `#[token]`s
+// also return the span of that attribute, where the errors in the lexer are located if no method or `#[token]` is related
+fn read_lex(attrs: &[Attribute], cfg: &Config) -> Result<(Option<String>, Span)> {
+  let span = attr_span(attrs, "lex").or_else(|| attr_span(attrs, "lex_path")).or_else(|| attr_span(attrs, "token")).unwrap_or_else(Span::call_site);
+  match (&cfg.lex, &cfg.lex_path) {
+    (Some(lex), _) => Ok((Some(lex.clone()), span)),
+    (None, Some(path)) => std::fs::read_to_string(path).map(|x| (Some(x), span))
+      .map_err(|e| Error::new(span, format!("failed to read lex \"{}\": {}", path, e))),
+    (None, None) => Ok((None, span)),
+  }
+}
+
+// parse the lexer toml (if any), then add the `#[token]`s in `attrs` to it
+fn parse_lex<'a>(lex: Option<&'a str>, span: Span, attrs: &[Attribute], arena: &'a Arena<u8>) -> Result<(RawLexer<'a>, Vec<token::TokenSpan>)> {
+  let mut raw = match lex {
+    Some(lex) => toml::from_str::<RawLexer>(lex).map_err(|e| Error::new(span, format!("failed to parse lexer toml: {}", e)))?,
+    None => RawLexer::default(),
+  };
+  let tokens = token::work(attrs, &mut raw, arena)?;
+  if lex.is_none() && tokens.is_empty() { return Err(Error::new(Span::call_site(), "attributes must contain `lex`, `lex_path` or `#[token]`")); }
+  Ok((raw, tokens))
+}
+
+fn grammar<'a>(lex: RawLexer<'a>, start: &'a str, production: Vec<RawProduction<'a>>, parser_def: Option<&'a str>) -> RawGrammar<'a> {
+  RawGrammar {
     include: "",
+    lex_path: None,
     priority: lex.priority,
+    definitions: lex.definitions,
     lexical: lex.lexical,
+    lexer_mode: lex.lexer_mode,
     lexer_field: lex.lexer_field,
     lexer_action: lex.lexer_action,
+    unicode: lex.unicode,
+    tab_width: lex.tab_width,
+    indent: lex.indent,
+    fallback: lex.fallback,
+    term_value: lex.term_value,
+    error_ty: lex.error_ty,
+    locations: lex.locations,
     parser_field: "",
     start,
     production,
-    parser_def: Some(&parser_def),
+    parser_def,
     epilogue: Some(""),
+  }
+}
+
+fn work(attr: TokenStream, input: TokenStream, algo: PGAlgo) -> Result<TokenStream2> {
+  let start = &attr.to_string();
+  // on a module, the grammar is built from the enums and structs in it, like #[derive(Lalr1)]
+  let parser = match syn::parse::<Item>(input)? {
+    Item::Impl(parser) => parser,
+    Item::Mod(m) => return derive::work_mod(start, m, algo),
+    item => return Err(Error::new_spanned(item, "expect an impl block or a module")),
+  };
+  let parser_def = parser.self_ty.to_token_stream().to_string();
+
+  let attrs = parser.attrs.iter().filter(|x| !x.path.is_ident("token")).cloned().collect::<Vec<_>>();
+  let cfg = Config::from_list(&parse_attrs(&attrs)?).map_err(|e| Error::new_spanned(&parser.self_ty, format!("failed to read attributes: {}", e)))?;
+  let (lex, lex_span) = read_lex(&parser.attrs, &cfg)?;
+  let arena = Arena::new();
+  let (lex, tokens) = parse_lex(lex.as_deref(), lex_span, &parser.attrs, &arena)?;
+  let recursive_descent = cfg.recursive_descent;
+
+  let mut production = Vec::new();
+  let mut rules = Vec::new();
+  for item in &parser.items {
+    if let ImplItem::Method(method) = item {
+      let name = &method.sig.ident;
+      // a method returning `Result<T, error_ty>` is a fallible action of type T
+      let (ty, fallible) = match &method.sig.output {
+        ReturnType::Default => ("()", false),
+        ReturnType::Type(_, ty) => match (split_result(ty), lex.error_ty) {
+          (Some((t, e)), Some(error_ty)) if no_space(&e.to_token_stream().to_string()) == no_space(error_ty) =>
+            (&*arena.alloc_str(&t.to_token_stream().to_string()), true),
+          _ => (&*arena.alloc_str(&ty.to_token_stream().to_string()), false),
+        }
+      };
+      // actions are inlined into the methods of the parser, which have `&mut self`, so `&self` and `&mut self` work as they are
+      let skip_self = match method.sig.inputs.first() {
+        Some(FnArg::Receiver(r)) if r.reference.is_none() =>
+          return Err(Error::new_spanned(r, format!("method `{}` takes `self` by value, it can only take `&self` or `&mut self`", name))),
+        Some(FnArg::Receiver(_)) => 1,
+        _ if uses_self(method.block.to_token_stream()) =>
+          return Err(Error::new_spanned(&method.sig, format!("method `{}` uses `self`, but doesn't declare `&self` or `&mut self`", name))),
+        _ => 0,
+      };
+      // arguments of type `&mut Lexer` and `&mut Token` are bound to the lexer and the lookahead token, they don't correspond to rhs
+      // an argument of type `Span` is bound to the location of lhs
+      let mut bind = String::new();
+      let mut rhs_arg = Vec::new();
+      for arg in method.sig.inputs.iter().skip(skip_self) {
+        match parse_arg(arg) {
+          None => return Err(Error::new_spanned(arg, format!("method `{}` takes `self` at illegal position", name))),
+          Some((pat, ty)) if ty.starts_with("& mut Lexer") => { let _ = write!(bind, "let {}=&mut *lexer;", pat); }
+          Some((pat, ty)) if ty.starts_with("& mut Token") => {
+            if algo == PGAlgo::LL1 && !recursive_descent {
+              return Err(Error::new_spanned(arg, format!("method `{}` takes the lookahead token, which is not supported by table-driven #[ll1]", name)));
+            }
+            // the lookahead is `token: &mut Token` in recursive descent parser, and `mut token: Token` in others
+            let _ = write!(bind, "let {}={};", pat, if recursive_descent { "&mut *token" } else { "&mut token" });
+          }
+          Some((pat, ty)) if ty == "Span" => {
+            if !lex.locations { return Err(Error::new_spanned(arg, format!("method `{}` takes `Span`, which requires `locations = true` in lex", name))); }
+            let _ = write!(bind, "let {}=_loc;", pat);
+          }
+          Some((pat, ty)) => rhs_arg.push((&*arena.alloc_str(&pat), &*arena.alloc_str(&ty))),
+        }
+      }
+      let block = method.block.to_token_stream().to_string();
+      let act = if bind.is_empty() { arena.alloc_str(&block) } else { arena.alloc_str(&format!("{{{}{}}}", bind, block)) };
+      // each rule is an alternative with the same action, the arguments are bound to its rhs symbols by position
+      for (rule, prec, rule_span, prec_span) in method_rules(method)? {
+        let rule = &*arena.alloc_str(&rule);
+        let prec = prec.map(|x| &*arena.alloc_str(&x));
+        let (lhs, rhs) = parse_arrow_prod(rule).ok_or_else(||
+          Error::new(rule_span, format!("rule \"{}\" of method `{}` is not in the form of \"lhs -> rhs1 rhs2 ...\"", rule, name)))?;
+        rules.push(RuleSpan { lhs, rhs: rhs.clone(), prec, rule_span, prec_span });
+        production.push(RawProduction { lhs, ty, rhs: vec![RawProductionRhs { rhs, rhs_arg: Some(rhs_arg.clone()), act, prec, fallible }] });
+      }
+    } else { return Err(Error::new_spanned(item, "only support method impl")); }
+  }
+
+  generate(&cfg, grammar(lex, start, production, Some(&parser_def)), algo, &rules, &tokens, lex_span)
+}
+
+// generate the parser, `rules`, `tokens` and `lex_span` are used to locate the errors and warnings
+fn generate(cfg: &Config, g: RawGrammar, algo: PGAlgo, rules: &[RuleSpan], tokens: &[token::TokenSpan], lex_span: Span) -> Result<TokenStream2> {
+  let Config { verbose, show_fsm, show_dfa, log_token, log_reduce, use_unsafe, recursive_descent, direct_lr, direct_lexer, expand, deny_conflicts, .. } = cfg;
+  let cfg = parser_gen::Config {
+    verbose: verbose.as_deref(),
+    show_fsm: show_fsm.as_deref(),
+    show_dfa: show_dfa.as_deref(),
+    log_token: *log_token,
+    log_reduce: *log_reduce,
+    use_unsafe: *use_unsafe,
+    recursive_descent: *recursive_descent,
+    direct_lr: *direct_lr,
+    direct_lexer: *direct_lexer,
+    ..parser_gen::Config::new(Lang::Rs, Vec::new())
   };
-  parser_gen::work(g, algo, &mut cfg).expect("failed to generate code");
-  let code = unsafe { String::from_utf8_unchecked(cfg.code_output) }; // must be valid utf-8
-  if expand { println!("{}", code); }
-  code.parse().unwrap()
+  let mut gen = Collect { cfg, conflicts: Vec::new(), warnings: Vec::new(), rules, tokens, lex_span, error: None };
+  if let Err(e) = parser_gen::work(g, algo, &mut gen) {
+    return Err(gen.error.take().unwrap_or_else(|| Error::new(Span::call_site(), format!("failed to generate code: {}", e))));
+  }
+  if *deny_conflicts && !gen.conflicts.is_empty() {
+    let mut errors = gen.conflicts.iter().map(|c| Error::new(locate(c, rules).unwrap_or_else(Span::call_site), c));
+    let mut error = errors.next().unwrap();
+    errors.for_each(|e| error.combine(e));
+    return Err(error);
+  }
+  let code = unsafe { String::from_utf8_unchecked(gen.cfg.code_output) }; // must be valid utf-8
+  if *expand { println!("{}", code); }
+  let mut code = code.parse::<TokenStream2>().map_err(|e| Error::new(Span::call_site(), format!("failed to parse generated code: {:?}", e)))?;
+  for w in &gen.warnings { code.extend(warning(w, token::locate(w, tokens).unwrap_or(lex_span))); }
+  for c in &gen.conflicts { code.extend(warning(c, locate(c, rules).unwrap_or_else(Span::call_site))); }
+  Ok(code)
 }
 
 #[proc_macro_attribute]
-pub fn lalr1(attr: TokenStream, input: TokenStream) -> TokenStream { work(attr, input, PGAlgo::LALR1) }
+pub fn lalr1(attr: TokenStream, input: TokenStream) -> TokenStream {
+  work(attr, input, PGAlgo::LALR1).unwrap_or_else(|e| e.to_compile_error()).into()
+}
 
 #[proc_macro_attribute]
-pub fn ll1(attr: TokenStream, input: TokenStream) -> TokenStream { work(attr, input, PGAlgo::LL1) }
\ No newline at end of file
+pub fn ll1(attr: TokenStream, input: TokenStream) -> TokenStream {
+  work(attr, input, PGAlgo::LL1).unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+// the helper attributes are `rule`, `token` and the fields of `Config`
+#[proc_macro_derive(Lalr1, attributes(rule, token, lex, lex_path, verbose, show_fsm, show_dfa, log_token, log_reduce, use_unsafe,
+  recursive_descent, direct_lr, direct_lexer, expand, deny_conflicts))]
+pub fn derive_lalr1(input: TokenStream) -> TokenStream {
+  syn::parse::<syn::DeriveInput>(input).and_then(derive::work).unwrap_or_else(|e| e.to_compile_error()).into()
+}
@@ -0,0 +1,82 @@
+// #[token(Name = "regex", prec = 1, assoc = "left")]: declare the lexical rules and priorities in attributes, instead of the `lex` toml
+use crate::*;
+use std::{borrow::Cow, collections::BTreeMap};
+use syn::{Lit, Meta};
+
+// a `#[token]` attribute, used to locate the messages about its regex or term
+pub struct TokenSpan { pub re: Option<String>, pub term: String, pub span: Span }
+
+// `Assoc` is not `FromMeta`, and `prec` is an integer
+fn parse_token(attr: &Attribute) -> Result<(String, Option<String>, Option<u32>, Option<Assoc>)> {
+  let err = |span: Span| Error::new(span, "expect #[token(Name = \"regex\")], optionally followed by `prec = 1` and `assoc = \"left\"`, or #[token(Name, prec = 1)]");
+  let list = match attr.parse_meta()? { Meta::List(x) => x, _ => return Err(err(attr.span())) };
+  let mut it = list.nested.iter();
+  let (term, re) = match it.next() {
+    Some(NestedMeta::Meta(Meta::NameValue(nv))) => match (nv.path.get_ident(), &nv.lit) {
+      (Some(name), Lit::Str(re)) => (name.to_string(), Some(re.value())),
+      _ => return Err(err(nv.span())),
+    }
+    Some(NestedMeta::Meta(Meta::Path(p))) => match p.get_ident() {
+      Some(name) => (name.to_string(), None),
+      None => return Err(err(p.span())),
+    }
+    _ => return Err(err(attr.span())),
+  };
+  let (mut prec, mut assoc) = (None, None);
+  for x in it {
+    match x {
+      NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("prec") && prec.is_none() => match &nv.lit {
+        Lit::Int(i) => prec = Some(i.base10_parse::<u32>()?),
+        lit => return Err(Error::new_spanned(lit, "`prec` should be an integer")),
+      }
+      NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("assoc") && assoc.is_none() => assoc = Some(match &nv.lit {
+        Lit::Str(s) if s.value() == "left" => Assoc::Left,
+        Lit::Str(s) if s.value() == "right" => Assoc::Right,
+        Lit::Str(s) if s.value() == "no_assoc" => Assoc::NoAssoc,
+        lit => return Err(Error::new_spanned(lit, "`assoc` should be \"left\", \"right\" or \"no_assoc\"")),
+      }),
+      _ => return Err(err(x.span())),
+    }
+  }
+  match (&re, prec, assoc) {
+    (None, None, _) => Err(Error::new(attr.span(), format!("#[token({})] without regex should have `prec`", term))),
+    (_, None, Some(_)) => Err(Error::new(attr.span(), format!("`assoc` of #[token({})] requires `prec`", term))),
+    _ => Ok((term, re, prec, assoc)),
+  }
+}
+
+// append the lexical rules and priorities in `#[token]`s to `lex`, after those already in it
+// the terms with the same `prec` form a priority row, the larger `prec` is, the higher the row is
+pub fn work<'a>(attrs: &[Attribute], lex: &mut RawLexer<'a>, arena: &'a Arena<u8>) -> Result<Vec<TokenSpan>> {
+  let mut spans = Vec::new();
+  // the assoc is only given by some of the terms in a row, so it is `None` until one does
+  let mut rows = BTreeMap::<u32, (Option<Assoc>, Vec<&'a str>)>::new();
+  for attr in attrs.iter().filter(|x| x.path.is_ident("token")) {
+    let (term, re, prec, assoc) = parse_token(attr)?;
+    let term = &*arena.alloc_str(&term);
+    if let Some(re) = &re {
+      let re = Cow::Borrowed(&*arena.alloc_str(re));
+      if lex.lexical.contains_key(&re) { return Err(Error::new(attr.span(), format!("duplicate lexical rule \"{}\"", re))); }
+      lex.lexical.insert(re, term);
+    }
+    if let Some(prec) = prec {
+      // a term with several regexes may repeat its prec
+      let (old, terms) = rows.entry(prec).or_insert((None, Vec::new()));
+      match (*old, assoc) {
+        (Some(old), Some(assoc)) if old != assoc => return Err(Error::new(attr.span(), format!("terms with prec {} have different assoc", prec))),
+        (None, Some(_)) => *old = assoc,
+        _ => {}
+      }
+      if !terms.contains(&term) { terms.push(term); }
+    }
+    spans.push(TokenSpan { re, term: term.to_owned(), span: attr.span() });
+  }
+  lex.priority.extend(rows.into_iter().map(|(_, (assoc, terms))| RawPriorityRow { assoc: assoc.unwrap_or(Assoc::Left), terms }));
+  Ok(spans)
+}
+
+// locate the message about a lexical rule (quoted regex) or a term (quoted name) at its `#[token]`
+pub fn locate(msg: &str, tokens: &[TokenSpan]) -> Option<Span> {
+  msg.split('"').skip(1).step_by(2).find_map(|q|
+    tokens.iter().find(|t| t.re.as_deref() == Some(q)).or_else(|| tokens.iter().find(|t| t.term == q)).map(|t| t.span))
+}
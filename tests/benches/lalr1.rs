@@ -0,0 +1,64 @@
+// compare the table-driven LR parser & lexer with the directly-coded ones (`#[direct_lr]`, `#[direct_lexer]`) on the grammar of `src/lalr1.rs`
+// `direct_lr` only encodes the parser, so the two attributes can be measured separately
+// run it with `cargo bench -p tests`
+use std::time::Instant;
+
+// the same parser under different attributes
+macro_rules! parser {
+  ($name: ident $(, $attr: ident)*) => {
+    mod $name {
+      use parser_macros::lalr1;
+
+      pub struct Parser;
+
+      #[lalr1(Expr)]
+      #[use_unsafe]
+      $(#[$attr])*
+      #[lex_path = "tests/src/lex.toml"]
+      impl Parser {
+        #[rule = "Expr -> Expr Add Expr"]
+        fn expr_add(l: i32, _op: Token, r: i32) -> i32 { l + r }
+        #[rule = "Expr -> Expr Sub Expr"]
+        fn expr_sub(l: i32, _op: Token, r: i32) -> i32 { l - r }
+        #[rule = "Expr -> Expr Mul Expr"]
+        fn expr_mul(l: i32, _op: Token, r: i32) -> i32 { l * r }
+        #[rule = "Expr -> Expr Div Expr"]
+        fn expr_div(l: i32, _op: Token, r: i32) -> i32 { l / r }
+        #[rule = "Expr -> Expr Mod Expr"]
+        fn expr_mod(l: i32, _op: Token, r: i32) -> i32 { l % r }
+        #[rule = "Expr -> Sub Expr"]
+        #[prec = "UMinus"]
+        fn expr_neg(_op: Token, r: i32) -> i32 { -r }
+        #[rule = "Expr -> LPar Expr RPar"]
+        fn expr_paren(_l: Token, i: i32, _r: Token) -> i32 { i }
+        #[rule = "Expr -> IntLit"]
+        fn expr_int(i: Token) -> i32 { std::str::from_utf8(i.piece).unwrap().parse().unwrap() }
+      }
+    }
+  };
+}
+
+parser!(table);
+parser!(direct_lr, direct_lr);
+parser!(direct, direct_lr, direct_lexer);
+
+const ROUND: usize = 20;
+
+fn main() {
+  let mut input = b"0".to_vec();
+  for _ in 0..100000 { input.extend_from_slice(b" + 1 - 2 * (3 + 4 * 5 / 6) + -7 * -9 % 10"); }
+  let expect = table::Parser.parse(&mut table::Lexer::new(&input));
+  assert_eq!(expect, Ok(-800000));
+  let expect = expect.map_err(|t| t.kind as u32);
+  assert_eq!(direct_lr::Parser.parse(&mut direct_lr::Lexer::new(&input)).map_err(|t| t.kind as u32), expect);
+  assert_eq!(direct::Parser.parse(&mut direct::Lexer::new(&input)).map_err(|t| t.kind as u32), expect);
+  let t = Instant::now();
+  for _ in 0..ROUND { let _ = table::Parser.parse(&mut table::Lexer::new(&input)); }
+  println!("table-driven: {:?}/iter", t.elapsed() / ROUND as u32);
+  let t = Instant::now();
+  for _ in 0..ROUND { let _ = direct_lr::Parser.parse(&mut direct_lr::Lexer::new(&input)); }
+  println!("directly-coded parser, table-driven lexer: {:?}/iter", t.elapsed() / ROUND as u32);
+  let t = Instant::now();
+  for _ in 0..ROUND { let _ = direct::Parser.parse(&mut direct::Lexer::new(&input)); }
+  println!("directly-coded: {:?}/iter", t.elapsed() / ROUND as u32);
+}
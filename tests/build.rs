@@ -0,0 +1,7 @@
+// generate parsers with the default options of `parser_gen::build::Builder`, which are included in `src/build.rs` and `src/placeholder.rs`
+use parser_gen::build::Builder;
+
+fn main() {
+  Builder::new("../parser-gen/examples/calc.toml").generate().unwrap();
+  Builder::new("src/placeholder.toml").generate().unwrap();
+}
@@ -0,0 +1,104 @@
+// Generate parsers with `build::Builder` as in `build.rs`, the output is the JSON data model, so that it is easy to check
+// except `out_dir`, which uses the Rust parser generated by `tests/build.rs`
+
+use parser_gen::{*, build::*};
+use serde_json::Value;
+use std::{fs, path::PathBuf};
+
+const LEXER: &str = r#"
+priority = [
+  { assoc = 'left', terms = ['Add'] },
+  { assoc = 'left', terms = ['Mul'] },
+]
+
+[lexical]
+'\+' = 'Add'
+'\*' = 'Mul'
+'\d+' = 'IntConst'
+'\s+' = '_Eps'
+"#;
+
+// top level keys of the grammar, must come before the tables
+const HEADER: &str = "include = ''\nstart = 'Expr'\n";
+
+const PARSER: &str = r#"
+[[production]]
+lhs = 'Expr'
+ty = 'int'
+rhs = [
+  { rhs = ['Expr', 'Add', 'Expr'], act = '$$ = $1 + $3' },
+  { rhs = ['Expr', 'Mul', 'Expr'], act = '$$ = $1 * $3' },
+  { rhs = ['IntConst'], act = '$$ = int($1.piece)' },
+]
+"#;
+
+// a fresh directory for each test, since tests run in parallel
+fn dir(name: &str) -> PathBuf {
+  let dir = std::env::temp_dir().join(format!("parser_gen_build_{}_{}", name, std::process::id()));
+  let _ = fs::remove_dir_all(&dir);
+  fs::create_dir_all(&dir).unwrap();
+  dir
+}
+
+// without priority, `Expr Add Expr` and `Expr Mul Expr` are ambiguous
+fn ambiguous() -> String { format!("{}priority = []\n{}{}", HEADER, &LEXER[LEXER.find("[lexical]").unwrap()..], PARSER) }
+
+fn model(path: PathBuf) -> Value { serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap() }
+
+#[test]
+fn single_file() {
+  let dir = dir("single_file");
+  fs::write(dir.join("calc.toml"), format!("{}{}{}", HEADER, LEXER, PARSER)).unwrap();
+  let out = Builder::new(dir.join("calc.toml")).lang(Lang::Json).out_dir(&dir).conflict(ConflictPolicy::Deny).generate().unwrap();
+  assert_eq!(out, dir.join("calc.json"));
+  let m = model(out);
+  assert_eq!(m["prod"].as_array().unwrap().len(), 4);
+  assert!(m["conflict"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn lex_path() {
+  let dir = dir("lex_path");
+  fs::create_dir_all(dir.join("lex")).unwrap();
+  fs::write(dir.join("lex/calc.toml"), LEXER).unwrap();
+  // `lex_path` is relative to the grammar toml, not the working directory
+  fs::write(dir.join("calc.toml"), format!("lex_path = 'lex/calc.toml'\n{}{}", HEADER, PARSER)).unwrap();
+  let out = Builder::new(dir.join("calc.toml")).lang(Lang::Json).out_dir(&dir).out_name("model.json").generate().unwrap();
+  assert_eq!(out, dir.join("model.json"));
+  let m = model(out);
+  let terms = m["terms"].as_array().unwrap().iter().map(|t| t["name"].as_str().unwrap().to_owned()).collect::<Vec<_>>();
+  assert!(terms.iter().any(|t| t == "IntConst"));
+  assert!(m["conflict"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn conflict_ignore() {
+  let dir = dir("conflict_ignore");
+  fs::write(dir.join("calc.toml"), ambiguous()).unwrap();
+  let out = Builder::new(dir.join("calc.toml")).lang(Lang::Json).out_dir(&dir).conflict(ConflictPolicy::Ignore).generate().unwrap();
+  assert!(!model(out)["conflict"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn conflict_deny() {
+  let dir = dir("conflict_deny");
+  fs::write(dir.join("calc.toml"), ambiguous()).unwrap();
+  let e = Builder::new(dir.join("calc.toml")).lang(Lang::Json).out_dir(&dir).conflict(ConflictPolicy::Deny).generate().unwrap_err();
+  assert!(e.to_string().contains("conflict(s) in grammar"));
+  // the code is still generated
+  assert!(!model(dir.join("calc.json"))["conflict"].as_array().unwrap().is_empty());
+}
+
+
+// generated by `Builder::new("../parser-gen/examples/calc.toml").generate()` in `tests/build.rs`, into "{OUT_DIR}/calc.rs"
+// unlike the code from proc macros, the included code is linted like handwritten code, e.g., unused token kinds
+#[allow(warnings)]
+mod calc {
+  include!(concat!(env!("OUT_DIR"), "/calc.rs"));
+}
+
+#[test]
+fn out_dir() {
+  assert_eq!(calc::Parser {}.parse(&mut calc::Lexer::new(b"1 + 2 * (3 - -4) % 5")), Ok(5));
+  assert!(calc::Parser {}.parse(&mut calc::Lexer::new(b"1 +")).is_err());
+}
@@ -0,0 +1,61 @@
+// Generate the C calculator in `parser-gen/examples` with a prefix and a separate header, and run it with `cc`
+
+use parser_gen::*;
+use std::{fs, process::Command};
+
+const DRIVER: &str = r#"
+#include <stdio.h>
+#include <string.h>
+#include "calc.h"
+
+static void run(const char *s, size_t cap) {
+  calc_Parser p;
+  calc_Lexer l;
+  calc_StackItem stk[16];
+  int32_t res;
+  calc_Token err;
+  calc_lexer_init(&l, s, strlen(s));
+  switch (calc_parse(&p, &l, stk, cap, &res, &err)) {
+    case 0: printf("%d\n", res); break;
+    case 1: printf("%d %.*s %u %u\n", err.kind == calc_Mul, (int)err.len, err.piece, err.line, err.col); break;
+    default: printf("overflow\n"); break;
+  }
+}
+
+int main(void) {
+  run("1 + 2 * 3", 16);
+  run("(1 + 2) * -3 % 5", 16);
+  run("1 + * 2", 16);
+  run("((((1))))", 16);
+  // the initial state and the 4 `(`s fill the stack
+  run("((((1))))", 4);
+  return 0;
+}
+"#;
+
+#[test]
+fn calc() {
+  let raw = toml::from_str(include_str!("../../parser-gen/examples/calc_c.toml")).unwrap();
+  let mut cfg = Config {
+    header_output: Some(("calc.h", Vec::new())),
+    prefix: "calc_",
+    ..Config::new(Lang::C, Vec::new())
+  };
+  work(raw, PGAlgo::LALR1, &mut cfg).unwrap();
+  let (code, header) = (String::from_utf8(cfg.code_output).unwrap(), String::from_utf8(cfg.header_output.unwrap().1).unwrap());
+  assert!(code.contains("#include \"calc.h\""));
+  // the declarations are only in the header, and all global names have the prefix
+  assert!(header.contains("int calc_parse(calc_Parser *self") && !code.contains("typedef"));
+  assert!(!code.contains(" lexer_next(") && !code.contains("(Token"));
+  let dir = std::env::temp_dir().join(format!("parser_gen_c_{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(dir.join("calc.c"), code).unwrap();
+  fs::write(dir.join("calc.h"), header).unwrap();
+  fs::write(dir.join("main.c"), DRIVER).unwrap();
+  let out = Command::new("cc").current_dir(&dir).args(&["-std=c99", "-Wall", "-o", "calc", "calc.c", "main.c"])
+    .output().expect("failed to run cc");
+  assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+  let out = Command::new(dir.join("calc")).output().unwrap();
+  assert!(out.status.success());
+  assert_eq!(String::from_utf8(out.stdout).unwrap(), "7\n-4\n1 * 1 5\n1\noverflow\n");
+}
@@ -0,0 +1,30 @@
+// Test that a grammar with conflicts still generates a parser, the conflicts are reported as deprecation warnings
+// the warnings are expected here, so they are allowed
+#![allow(deprecated)]
+
+use parser_macros::lalr1;
+
+struct Parser;
+
+#[lalr1(Expr)]
+#[lex = r#"
+priority = []
+
+[lexical]
+'-' = 'Sub'
+'\d+' = 'IntLit'
+'\s+' = '_Eps'
+"#]
+impl Parser {
+  // without priority, "Expr -> Expr Sub Expr" has a shift-reduce conflict on `Sub`, and shift is selected
+  #[rule = "Expr -> Expr Sub Expr"]
+  fn expr_sub(l: i32, _op: Token, r: i32) -> i32 { l - r }
+  #[rule = "Expr -> IntLit"]
+  fn expr_int(i: Token) -> i32 { std::str::from_utf8(i.piece).unwrap().parse().unwrap() }
+}
+
+#[test]
+fn conflict() {
+  // right associative because of shift
+  assert_eq!(Parser.parse(&mut Lexer::new(b"1 - 2 - 3")), Ok(2));
+}
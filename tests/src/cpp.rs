@@ -0,0 +1,38 @@
+// Generate the C++ calculator in `parser-gen/examples` with the table-driven and the directly-coded lexer, and run them with `g++`
+
+use parser_gen::*;
+use std::{fs, process::Command};
+
+const DRIVER: &str = r#"
+#include <cstdio>
+
+int main() {
+  for (const char *s : {"1 + 2 * 3", "(10 + 2) * -3 % 5", "12 / (4 - 1)", "1 + $", "1 +\n * 2"}) {
+    Lexer lexer(s);
+    auto res = Parser{}.parse(lexer);
+    if (auto v = std::get_if<i32>(&res)) { printf("%d\n", *v); }
+    else { Token t = std::get<Token>(res); printf("%d %.*s %u %u\n", t.kind == Token::_Eof, (int)t.piece.size(), t.piece.data(), t.line, t.col); }
+  }
+}
+"#;
+
+fn run(direct_lexer: bool) {
+  let raw = toml::from_str(include_str!("../../parser-gen/examples/calc_cpp.toml")).unwrap();
+  let mut cfg = Config { direct_lexer, ..Config::new(Lang::Cpp, Vec::new()) };
+  work(raw, PGAlgo::LALR1, &mut cfg).unwrap();
+  let dir = std::env::temp_dir().join(format!("parser_gen_cpp_{}_{}", direct_lexer, std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(dir.join("calc.cpp"), [cfg.code_output, DRIVER.as_bytes().to_vec()].concat()).unwrap();
+  let out = Command::new("g++").current_dir(&dir).args(&["-std=c++17", "-Wall", "-o", "calc", "calc.cpp"]).output().expect("failed to run g++");
+  assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+  let out = Command::new(dir.join("calc")).output().unwrap();
+  assert!(out.status.success());
+  // `$` is not accepted by any rule, so the lexer stops there and returns `_Eof`
+  assert_eq!(String::from_utf8(out.stdout).unwrap(), "7\n-1\n4\n1  1 5\n0 * 2 2\n");
+}
+
+#[test]
+fn table_lexer() { run(false); }
+
+#[test]
+fn direct_lexer() { run(true); }
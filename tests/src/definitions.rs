@@ -0,0 +1,46 @@
+use parser_macros::lalr1;
+
+// Test named regex definitions in lexical rules
+
+#[allow(unused)]
+struct Parser;
+
+#[lalr1(Expr)]
+#[lex = r#"
+priority = []
+
+[definitions]
+DIGIT = '[0-9]'
+EXP = '[eE][+-]?{DIGIT}+'
+ID_START = '[A-Za-z_]'
+
+[lexical]
+'{DIGIT}+' = 'Int'
+'{DIGIT}+\.{DIGIT}*({EXP})?|{DIGIT}+{EXP}' = 'Float'
+'{ID_START}({ID_START}|{DIGIT})*' = 'Id'
+'\{' = 'LBrc'
+'[{]' = 'LBrc'
+'\s+' = '_Eps'
+"#]
+impl Parser {
+  #[rule = "Expr -> Int"]
+  fn expr(_: Token) -> () {} // not using Parser in this test
+}
+
+fn assert(t: Token, kind: TokenKind, piece: &[u8]) {
+  assert_eq!(t.kind, kind);
+  assert_eq!(t.piece, piece);
+}
+
+#[test]
+fn definitions() {
+  use TokenKind::*;
+  let mut l = Lexer::new(b"12 3.5e+10 7E2 x_1 { _9");
+  assert(l.next(), Int, b"12");
+  assert(l.next(), Float, b"3.5e+10");
+  assert(l.next(), Float, b"7E2");
+  assert(l.next(), Id, b"x_1");
+  assert(l.next(), LBrc, b"{");
+  assert(l.next(), Id, b"_9");
+  assert(l.next(), _Eof, b"");
+}
@@ -0,0 +1,114 @@
+use parser_macros::Lalr1;
+
+// Test #[derive(Lalr1)]: each variant is a production of `Expr`, the fields are bound by `{0}`/`{name}` in the pattern,
+// and literals are mapped to the terms of their lexical rules, or new terms if not found (`'abs'`)
+
+#[derive(Lalr1, Debug, PartialEq)]
+#[lex_path = "tests/src/lex.toml"]
+enum Expr<'p> {
+  #[rule("{0} '+' {1}")]
+  Add(Box<Expr<'p>>, Box<Expr<'p>>),
+  #[rule("{l} Sub {r}")]
+  Sub { l: Box<Expr<'p>>, r: Box<Expr<'p>> },
+  #[rule("{0} '*' {1}")]
+  Mul(Box<Expr<'p>>, Box<Expr<'p>>),
+  #[rule("'-' {0}", prec = "UMinus")]
+  Neg(Box<Expr<'p>>),
+  #[rule("'abs' '(' {0} ')'")]
+  Abs(Box<Expr<'p>>),
+  #[rule("'(' {0} ')'")]
+  Paren(Box<Expr<'p>>),
+  #[rule("{0:IntLit}")]
+  Int(Token<'p>),
+}
+
+fn eval(e: &Expr) -> i32 {
+  match e {
+    Expr::Add(l, r) => eval(l) + eval(r),
+    Expr::Sub { l, r } => eval(l) - eval(r),
+    Expr::Mul(l, r) => eval(l) * eval(r),
+    Expr::Neg(x) => -eval(x),
+    Expr::Abs(x) => eval(x).abs(),
+    Expr::Paren(x) => eval(x),
+    Expr::Int(t) => std::str::from_utf8(t.piece).unwrap().parse().unwrap(),
+  }
+}
+
+mod pair {
+  use parser_macros::Lalr1;
+
+  #[derive(Lalr1)]
+  #[lex = r#"
+priority = []
+
+[lexical]
+'=' = 'Eq'
+'\w+' = 'Id'
+'\s+' = '_Eps'
+"#]
+  #[rule("{key:Id} '=' {value:Id}")]
+  pub struct Pair<'p> {
+    pub key: Token<'p>,
+    pub value: Token<'p>,
+  }
+
+  #[test]
+  fn pair() {
+    let p = Parser {}.parse(&mut Lexer::new(b"a = b")).unwrap();
+    assert_eq!((p.key.piece, p.value.piece), (&b"a"[..], &b"b"[..]));
+  }
+}
+
+// #[lalr1(Block)] on a module: the enums and structs with #[rule] in it make up the grammar, so they can refer to each other,
+// and `{0:Stmt*}` is a `Vec` of zero or more `Stmt`s (`+` for one or more)
+#[parser_macros::lalr1(Block)]
+#[lex = r#"
+priority = []
+
+[lexical]
+'\{' = 'LBrace'
+'\}' = 'RBrace'
+'=' = 'Eq'
+';' = 'Semi'
+'\d+' = 'Int'
+'[a-z]+' = 'Id'
+'\s+' = '_Eps'
+"#]
+mod block {
+  #[rule("LBrace {0:Stmt*} RBrace")]
+  pub struct Block<'p>(pub Vec<Stmt<'p>>);
+
+  pub enum Stmt<'p> {
+    #[rule("{0:Id} '=' {1:Int+} ';'")]
+    Assign(Token<'p>, Vec<Token<'p>>),
+    #[rule("{0}")]
+    Block(Block<'p>),
+  }
+
+  #[test]
+  fn block() {
+    let b = Parser {}.parse(&mut Lexer::new(b"{ a = 1 2; {} { b = 3; } }")).unwrap();
+    assert_eq!(b.0.len(), 3);
+    match (&b.0[0], &b.0[1], &b.0[2]) {
+      (Stmt::Assign(a, v), Stmt::Block(Block(e)), Stmt::Block(Block(s))) => {
+        assert_eq!(a.piece, b"a");
+        assert_eq!(v.iter().map(|x| x.piece).collect::<Vec<_>>(), [b"1", b"2"]);
+        assert!(e.is_empty());
+        assert!(matches!(&s[..], [Stmt::Assign(..)]));
+      }
+      _ => panic!("expect Assign, Block, Block"),
+    }
+    assert!(Parser {}.parse(&mut Lexer::new(b"{ a = ; }")).is_err());
+  }
+}
+
+#[test]
+fn derive() {
+  let e = Parser {}.parse(&mut Lexer::new(b"1 - 2 * abs(3 - 5) + -(4)")).unwrap();
+  assert_eq!(eval(&e), -7);
+  match e {
+    Expr::Add(l, _) => assert!(matches!(*l, Expr::Sub { .. })),
+    _ => panic!("expect Add at top"),
+  }
+  assert!(Parser {}.parse(&mut Lexer::new(b"1 +")).is_err());
+}
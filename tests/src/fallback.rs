@@ -0,0 +1,45 @@
+// Test contextual keywords with fallback terms, `async` is a keyword only at the beginning of a statement
+
+mod table {
+  use parser_macros::lalr1;
+
+  pub struct Parser;
+
+  fn s(t: Token) -> String { std::str::from_utf8(t.piece).unwrap().to_owned() }
+
+  #[lalr1(Stmt)]
+  #[lex_path = "tests/src/fallback.toml"]
+  impl Parser {
+    #[rule = "Stmt -> Async Id"]
+    fn stmt_async(_a: Token, f: Token) -> String { format!("async {}", s(f)) }
+    #[rule = "Stmt -> Id Assign Id"]
+    fn stmt_assign(l: Token, _a: Token, r: Token) -> String { format!("{} = {}", s(l), s(r)) }
+  }
+}
+
+mod direct {
+  use parser_macros::lalr1;
+
+  pub struct Parser;
+
+  fn s(t: Token) -> String { std::str::from_utf8(t.piece).unwrap().to_owned() }
+
+  #[lalr1(Stmt)]
+  #[direct_lr]
+  #[lex_path = "tests/src/fallback.toml"]
+  impl Parser {
+    #[rule = "Stmt -> Async Id"]
+    fn stmt_async(_a: Token, f: Token) -> String { format!("async {}", s(f)) }
+    #[rule = "Stmt -> Id Assign Id"]
+    fn stmt_assign(l: Token, _a: Token, r: Token) -> String { format!("{} = {}", s(l), s(r)) }
+  }
+}
+
+#[test]
+fn fallback() {
+  for (input, output) in &[("async foo", "async foo"), ("async async", "async async"), ("x = async", "x = async")] {
+    assert_eq!(table::Parser.parse(&mut table::Lexer::new(input.as_bytes())).as_deref(), Ok(*output));
+    assert_eq!(direct::Parser.parse(&mut direct::Lexer::new(input.as_bytes())).as_deref(), Ok(*output));
+  }
+  assert_eq!(table::Parser.parse(&mut table::Lexer::new(b"async =")).unwrap_err().kind, table::TokenKind::Assign);
+}
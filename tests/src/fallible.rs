@@ -0,0 +1,59 @@
+// Test fallible actions: a method returning `Result<T, error_ty>` aborts parsing with `ParseError::User` on `Err`
+
+// the same parser under the attributes of each kind of parser: table-driven LR, directly-coded LR and recursive descent
+macro_rules! parser {
+  ($name: ident, $algo: ident $(, $attr: ident)*) => {
+    mod $name {
+      use parser_macros::$algo;
+
+      pub struct Parser;
+
+      #[$algo(Expr)]
+      $(#[$attr])*
+      #[lex_path = "tests/src/fallible.toml"]
+      impl Parser {
+        #[rule = "Expr -> Int Rest"]
+        fn expr(i: Token, r: Option<u8>) -> Result<u8, String> {
+          // `?` and `return` leave the action, not the parser
+          let i = std::str::from_utf8(i.piece).unwrap().parse::<u8>().map_err(|e| e.to_string())?;
+          let r = match r { Some(r) => r, None => return Ok(i) };
+          i.checked_add(r).ok_or_else(|| format!("{} + {} overflows", i, r))
+        }
+        #[rule = "Rest ->"]
+        fn rest0() -> Option<u8> { None }
+        #[rule = "Rest -> Add Expr"]
+        fn rest1(_a: Token, e: u8) -> Option<u8> { Some(e) }
+      }
+    }
+  };
+}
+
+parser!(table, lalr1);
+parser!(direct, lalr1, direct_lr);
+parser!(rd, ll1, recursive_descent);
+
+macro_rules! check {
+  ($m: ident) => {{
+    use $m::*;
+    assert_eq!(Parser.parse(&mut Lexer::new(b"1 + 2 + 100")).unwrap(), 103);
+    match Parser.parse(&mut Lexer::new(b"1 + 200 + 100")) {
+      Err(ParseError::User(e)) => assert_eq!(e, "200 + 100 overflows"),
+      _ => panic!("expect a user error"),
+    }
+    match Parser.parse(&mut Lexer::new(b"1 + 300")) {
+      Err(ParseError::User(e)) => assert_eq!(e, "number too large to fit in target type"),
+      _ => panic!("expect a user error"),
+    }
+    match Parser.parse(&mut Lexer::new(b"1 + + 2")) {
+      Err(ParseError::Syntax(t)) => assert_eq!((t.kind, t.col), (TokenKind::Add, 5)),
+      _ => panic!("expect a syntax error"),
+    }
+  }};
+}
+
+#[test]
+fn fallible() {
+  check!(table);
+  check!(direct);
+  check!(rd);
+}
@@ -0,0 +1,45 @@
+// Generate the Go calculator in `parser-gen/examples`, check it with `gofmt -l` and run it with `go run`
+
+use parser_gen::*;
+use std::{fs, process::Command};
+
+const DRIVER: &str = r#"package main
+
+import (
+	"fmt"
+
+	"calc/parser"
+)
+
+func main() {
+	for _, s := range []string{"1 + 2 * 3", "(1 + 2) * -3 % 5", "1 +\n * 2"} {
+		p := parser.Parser{}
+		if res, err := p.Parse(parser.NewLexer([]byte(s))); err == nil {
+			fmt.Println(res)
+		} else {
+			fmt.Println(err.(*parser.SyntaxError).Token.Kind == parser.Mul, err)
+		}
+	}
+}
+"#;
+
+// unlike python3 and cc, go is not always installed, so it is only run with `--ignored`
+#[test]
+#[ignore = "needs go"]
+fn calc() {
+  let raw = toml::from_str(include_str!("../../parser-gen/examples/calc_go.toml")).unwrap();
+  let mut cfg = Config::new(Lang::Go, Vec::new());
+  work(raw, PGAlgo::LALR1, &mut cfg).unwrap();
+  let dir = std::env::temp_dir().join(format!("parser_gen_go_{}", std::process::id()));
+  fs::create_dir_all(dir.join("parser")).unwrap();
+  fs::write(dir.join("parser/calc.go"), cfg.code_output).unwrap();
+  fs::write(dir.join("main.go"), DRIVER).unwrap();
+  fs::write(dir.join("go.mod"), "module calc\n\ngo 1.13\n").unwrap();
+  // gofmt lists the files whose formatting differs from gofmt's
+  let out = Command::new("gofmt").current_dir(&dir).args(&["-l", "parser/calc.go"]).output().expect("failed to run gofmt");
+  assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+  assert_eq!(String::from_utf8(out.stdout).unwrap(), "");
+  let out = Command::new("go").current_dir(&dir).args(&["run", "."]).output().expect("failed to run go");
+  assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+  assert_eq!(String::from_utf8(out.stdout).unwrap(), "7\n-4\ntrue 2:2: unexpected token \"*\"\n");
+}
@@ -0,0 +1,61 @@
+use parser_macros::lalr1;
+
+// Test indentation-sensitive lexing with a python-like config language
+
+struct Parser;
+
+fn s(t: Token) -> String { std::str::from_utf8(t.piece).unwrap().to_owned() }
+
+#[lalr1(Items)]
+#[lex = r#"
+priority = []
+indent = { indent = 'Indent', dedent = 'Dedent', newline = 'Newline', open = ['LPar'], close = ['RPar'] }
+
+[lexical]
+':' = 'Colon'
+',' = 'Comma'
+'\(' = 'LPar'
+'\)' = 'RPar'
+'[a-z]+' = 'Id'
+'[0-9]+' = 'Int'
+'#[^\n]*' = '_Eps'
+'\s+' = '_Eps'
+"#]
+impl Parser {
+  #[rule = "Items ->"]
+  fn items0() -> String { String::new() }
+  #[rule = "Items -> Items Item"]
+  fn items1(mut l: String, r: String) -> String {
+    l += &r;
+    l
+  }
+  #[rule = "Item -> Id Colon Value Newline"]
+  fn item_value(k: Token, _c: Token, v: String, _n: Token) -> String { format!("{}={};", s(k), v) }
+  #[rule = "Item -> Id Colon Newline Indent Items Dedent"]
+  fn item_block(k: Token, _c: Token, _n: Token, _i: Token, items: String, _d: Token) -> String { format!("{}{{{}}}", s(k), items) }
+  #[rule = "Value -> Int"]
+  fn value_int(i: Token) -> String { s(i) }
+  #[rule = "Value -> LPar Ints RPar"]
+  fn value_tuple(_l: Token, i: String, _r: Token) -> String { format!("({})", i) }
+  #[rule = "Ints -> Int"]
+  fn ints0(i: Token) -> String { s(i) }
+  #[rule = "Ints -> Ints Comma Int"]
+  fn ints1(l: String, _c: Token, i: Token) -> String { format!("{},{}", l, s(i)) }
+}
+
+#[test]
+fn indent() {
+  let s = b"a: 1
+# comment
+
+b:
+  c: (1,
+2, 3)
+  d:
+    e: 4
+f: 5";
+  assert_eq!(Parser.parse(&mut Lexer::new(s)), Ok("a=1;b{c=(1,2,3);d{e=4;}}f=5;".to_owned()));
+  assert_eq!(Parser.parse(&mut Lexer::new(b"a:\n  b: 1\n")), Ok("a{b=1;}".to_owned()));
+  // inconsistent dedent
+  assert_eq!(Parser.parse(&mut Lexer::new(b"a:\n    b: 1\n  c: 2")).unwrap_err().kind, TokenKind::_Err);
+}
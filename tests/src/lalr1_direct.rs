@@ -0,0 +1,35 @@
+use parser_macros::lalr1;
+
+// the same grammar as lalr1.rs, but using the directly-coded parser and lexer
+
+struct Parser;
+
+#[lalr1(Expr)]
+#[direct_lr]
+#[direct_lexer]
+#[lex_path = "tests/src/lex.toml"]
+impl Parser {
+  #[rule = "Expr -> Expr Add Expr"]
+  fn expr_add(l: i32, _op: Token, r: i32) -> i32 { l + r }
+  #[rule = "Expr -> Expr Sub Expr"]
+  fn expr_sub(l: i32, _op: Token, r: i32) -> i32 { l - r }
+  #[rule = "Expr -> Expr Mul Expr"]
+  fn expr_mul(l: i32, _op: Token, r: i32) -> i32 { l * r }
+  #[rule = "Expr -> Expr Div Expr"]
+  fn expr_div(l: i32, _op: Token, r: i32) -> i32 { l / r }
+  #[rule = "Expr -> Expr Mod Expr"]
+  fn expr_mod(l: i32, _op: Token, r: i32) -> i32 { l % r }
+  #[rule = "Expr -> Sub Expr"]
+  #[prec = "UMinus"]
+  fn expr_neg(_op: Token, r: i32) -> i32 { -r }
+  #[rule = "Expr -> LPar Expr RPar"]
+  fn expr_paren(_l: Token, i: i32, _r: Token) -> i32 { i }
+  #[rule = "Expr -> IntLit"]
+  fn expr_int(i: Token) -> i32 { std::str::from_utf8(i.piece).unwrap().parse().unwrap() }
+}
+
+#[test]
+fn lalr1_direct() {
+  assert_eq!(Parser.parse(&mut Lexer::new(b"1 - 2 * (3 + 4 * 5 / 6) + -7 * -9 % 10")), Ok(-8));
+  assert_eq!(Parser.parse(&mut Lexer::new(b"1 + * 2")).map_err(|t| t.kind), Err(TokenKind::Mul));
+}
@@ -0,0 +1,69 @@
+// Check the warnings about lexical rules that never match, match the empty string, or are shadowed by earlier rules
+
+use parser_gen::*;
+use std::cell::RefCell;
+
+thread_local! {
+  // `on_lexical_warning` is a `fn`, so the warnings are collected here, tests run in different threads
+  static WARNINGS: RefCell<Vec<String>> = RefCell::new(Vec::new());
+}
+
+// return the lexical warnings and whether the parser is generated, `lexical` should define `Id`, which is the only rhs
+fn gen(lexical: &str) -> (Vec<String>, bool) {
+  let toml = format!(r#"
+include = ''
+priority = []
+start = 'Expr'
+
+[lexical]
+{}
+
+[[production]]
+lhs = 'Expr'
+ty = 'int'
+rhs = [{{ rhs = ['Id'], act = '' }}]
+"#, lexical);
+  let raw = toml::from_str(&toml).unwrap();
+  let mut cfg = Config {
+    on_lexical_warning: |w| WARNINGS.with(|x| x.borrow_mut().push(w)),
+    ..Config::new(Lang::Json, Vec::new())
+  };
+  // the warnings are reported before the errors about the dfa
+  let ok = work(raw, PGAlgo::LALR1, &mut cfg).is_ok();
+  (WARNINGS.with(|x| x.take()), ok)
+}
+
+#[test]
+fn never_matches() {
+  assert_eq!(gen(r"'[a-z]+' = 'Id'
+'if' = 'If'"), (vec![r#"lexical rule "if" (If) never matches, it is shadowed by earlier rules, e.g., lexical rule "[a-z]+" (Id) on "if""#.to_owned()], true));
+  // a keyword before the identifier is the intended usage
+  assert_eq!(gen(r"'if' = 'If'
+'[a-z]+' = 'Id'"), (vec![], true));
+}
+
+#[test]
+fn empty_string() {
+  let (warnings, ok) = gen(r"'[a-z]*' = 'Id'");
+  assert_eq!(warnings, [r#"lexical rule "[a-z]*" (Id) can match the empty string"#]);
+  // a lexer can't accept the empty string
+  assert!(!ok);
+}
+
+#[test]
+fn swallowed() {
+  // "int32" is still `Int`, but "int" is `Id`
+  assert_eq!(gen(r"'[a-z]+' = 'Id'
+'int|int32' = 'Int'"), (vec![r#"lexical rule "int|int32" (Int) is swallowed by lexical rule "[a-z]+" (Id) on "int", consider moving it before the latter"#.to_owned()], true));
+}
+
+#[test]
+fn partially_shadowed() {
+  // the example is the shortest string matched by the earlier rule
+  assert_eq!(gen(r"'[0-9]+' = 'Int'
+'[0-9]+(\.[0-9]*)?' = 'Float'
+'[a-z]+' = 'Id'"), (vec![r#"lexical rule "[0-9]+(\.[0-9]*)?" (Float) is partially shadowed by lexical rule "[0-9]+" (Int) on "0""#.to_owned()], true));
+  // the same term is not reported
+  assert_eq!(gen(r"'[0-9]+' = 'Id'
+'[0-9]+(\.[0-9]*)?' = 'Id'"), (vec![], true));
+}
@@ -0,0 +1,215 @@
+// Generate parsers with lexer modes and indentation for C++ and Java, and run them with `g++` and `javac`/`java`
+
+use parser_gen::*;
+use std::{fs, process::Command};
+
+// `production` is shared by both languages: `{ty}` is the string type, `{lhs}` is the value of lhs,
+// `{rhs}1`, `{rhs}2`... are the values of rhs, and `{piece}1`, `{piece}2`... are the pieces of rhs tokens as strings
+fn grammar(lexical: &str, production: &str, cpp: bool) -> String {
+  let (ty, lhs, rhs) = if cpp { ("std::string", "__", "_") } else { ("String", "$.$", "$") };
+  let mut production = production.replace("{ty}", ty).replace("{lhs}", lhs).replace("{rhs}", rhs);
+  for i in 1..=4 {
+    let piece = if cpp { format!("std::string(_{}.piece)", i) } else { format!("${}.piece", i) };
+    production = production.replace(&format!("{{piece}}{}", i), &piece);
+  }
+  format!("include = '{}'\npriority = []\n{}\n{}", if cpp { "#include <string>" } else { "" }, lexical, production)
+}
+
+// rust escapes in string literals also work in c++ and java
+fn literals(input: &[&str]) -> String {
+  input.iter().map(|s| format!("{:?}", s)).collect::<Vec<_>>().join(", ")
+}
+
+fn dir(name: &str) -> std::path::PathBuf {
+  let dir = std::env::temp_dir().join(format!("parser_gen_lexer_{}_{}", name, std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  dir
+}
+
+// parse each of `input` with the parser generated from `toml`, and return the output of the driver
+fn cpp(name: &str, toml: &str, input: &[&str]) -> String {
+  let raw = toml::from_str(toml).unwrap();
+  let mut cfg = Config::new(Lang::Cpp, Vec::new());
+  work(raw, PGAlgo::LALR1, &mut cfg).unwrap();
+  let driver = format!(r#"
+#include <cstdio>
+
+int main() {{
+  for (const char *s : {{{}}}) {{
+    Lexer lexer(s);
+    auto res = Parser{{}}.parse(lexer);
+    if (auto v = std::get_if<std::string>(&res)) {{ printf("%s\n", v->c_str()); }}
+    else {{ Token t = std::get<Token>(res); printf("%d %.*s %u %u\n", t.kind == Token::_Err, (int)t.piece.size(), t.piece.data(), t.line, t.col); }}
+  }}
+}}
+"#, literals(input));
+  let dir = dir(&format!("cpp_{}", name));
+  fs::write(dir.join("main.cpp"), [cfg.code_output, driver.into_bytes()].concat()).unwrap();
+  let out = Command::new("g++").current_dir(&dir).args(&["-std=c++17", "-Wall", "-o", "main", "main.cpp"]).output().expect("failed to run g++");
+  assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+  let out = Command::new(dir.join("main")).output().unwrap();
+  assert!(out.status.success());
+  String::from_utf8(out.stdout).unwrap()
+}
+
+fn java(name: &str, toml: &str, input: &[&str]) -> String {
+  let raw = toml::from_str(toml).unwrap();
+  let mut cfg = Config::new(Lang::Java, Vec::new());
+  work(raw, PGAlgo::LALR1, &mut cfg).unwrap();
+  // `ERR` is the kind of `_Err`
+  let driver = format!(r#"
+public class Main {{
+  public static void main(String[] args) {{
+    for (String s : new String[]{{{}}}) {{
+      Parser.Result r = new Parser().parse(new Parser.Lexer(s.getBytes()));
+      if (r.err == null) {{ System.out.println(r.ok); }}
+      else {{ System.out.println((r.err.kind == 2 ? 1 : 0) + " " + r.err.piece + " " + r.err.line + " " + r.err.col); }}
+    }}
+  }}
+}}
+"#, literals(input));
+  let dir = dir(&format!("java_{}", name));
+  fs::write(dir.join("Parser.java"), cfg.code_output).unwrap();
+  fs::write(dir.join("Main.java"), driver).unwrap();
+  let out = Command::new("javac").current_dir(&dir).args(&["Parser.java", "Main.java"]).output().expect("failed to run javac");
+  assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+  let out = Command::new("java").current_dir(&dir).args(&["-cp", ".", "Main"]).output().expect("failed to run java");
+  assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+  String::from_utf8(out.stdout).unwrap()
+}
+
+// nested block comments, and strings with escapes and interpolations, like `tests/src/lexer_mode.rs`
+const MODE_LEXICAL: &str = r#"
+start = 'Items'
+
+[lexical]
+'/\*' = '_Eps'
+'"' = 'StrBegin'
+'[a-z]+' = 'Id'
+'\s+' = '_Eps'
+
+[lexer_mode._Init.push]
+'/\*' = 'Comment'
+'"' = 'Str'
+
+[lexer_mode.Comment]
+pop = ['\*/']
+lexical = { '/\*' = '_Eps', '\*/' = '_Eps', '[^*/]+|\*|/' = '_Eps' }
+push = { '/\*' = 'Comment' }
+
+[lexer_mode.Str]
+lexical = { '[^"\\$]+' = 'StrPiece', '\\.' = 'StrEscape', '"' = 'StrEnd', '\$\{' = 'InterpBegin' }
+push = { '\$\{' = 'Interp' }
+pop = ['"']
+
+[lexer_mode.Interp]
+lexical = { '[a-z]+' = 'Id', '\}' = 'InterpEnd', '\s+' = '_Eps' }
+pop = ['\}']
+"#;
+
+const MODE_PRODUCTION: &str = r#"
+[[production]]
+lhs = 'Items'
+ty = '{ty}'
+rhs = [
+  { rhs = [], act = '{lhs} = "";' },
+  { rhs = ['Items', 'Item'], act = '{lhs} = {rhs}1 + {rhs}2;' },
+]
+
+[[production]]
+lhs = 'Item'
+ty = '{ty}'
+rhs = [
+  { rhs = ['Id'], act = '{lhs} = {piece}1;' },
+  { rhs = ['StrBegin', 'Pieces', 'StrEnd'], act = '{lhs} = "<" + {rhs}2 + ">";' },
+]
+
+[[production]]
+lhs = 'Pieces'
+ty = '{ty}'
+rhs = [
+  { rhs = [], act = '{lhs} = "";' },
+  { rhs = ['Pieces', 'StrPiece'], act = '{lhs} = {rhs}1 + {piece}2;' },
+  { rhs = ['Pieces', 'StrEscape'], act = '{lhs} = {rhs}1 + {piece}2;' },
+  { rhs = ['Pieces', 'InterpBegin', 'Id', 'InterpEnd'], act = '{lhs} = {rhs}1 + "{" + {piece}3 + "}";' },
+]
+"#;
+
+const MODE_INPUT: [&str; 2] = [r#"x /* b /* c */ d */ "a\"b${ y }c" z"#, "/**/a/*/**/*/b"];
+const MODE_OUTPUT: &str = "x<a\\\"b{y}c>z\nab\n";
+
+#[test]
+fn lexer_mode_cpp() {
+  let out = cpp("mode", &grammar(MODE_LEXICAL, MODE_PRODUCTION, true), &MODE_INPUT);
+  assert_eq!(out, MODE_OUTPUT);
+}
+
+#[test]
+fn lexer_mode_java() {
+  let out = java("mode", &grammar(MODE_LEXICAL, MODE_PRODUCTION, false), &MODE_INPUT);
+  assert_eq!(out, MODE_OUTPUT);
+}
+
+// a python-like config language, like `tests/src/indent.rs`
+const INDENT_LEXICAL: &str = r#"
+start = 'Items'
+indent = { indent = 'Indent', dedent = 'Dedent', newline = 'Newline', open = ['LPar'], close = ['RPar'] }
+
+[lexical]
+':' = 'Colon'
+',' = 'Comma'
+'\(' = 'LPar'
+'\)' = 'RPar'
+'[a-z]+' = 'Id'
+'[0-9]+' = 'Int'
+'#[^\n]*' = '_Eps'
+'\s+' = '_Eps'
+"#;
+
+const INDENT_PRODUCTION: &str = r#"
+[[production]]
+lhs = 'Items'
+ty = '{ty}'
+rhs = [
+  { rhs = [], act = '{lhs} = "";' },
+  { rhs = ['Items', 'Item'], act = '{lhs} = {rhs}1 + {rhs}2;' },
+]
+
+[[production]]
+lhs = 'Item'
+ty = '{ty}'
+rhs = [
+  { rhs = ['Id', 'Colon', 'Value', 'Newline'], act = '{lhs} = {piece}1 + "=" + {rhs}3 + ";";' },
+  { rhs = ['Id', 'Colon', 'Newline', 'Indent', 'Items', 'Dedent'], act = '{lhs} = {piece}1 + "{" + {rhs}5 + "}";' },
+]
+
+[[production]]
+lhs = 'Value'
+ty = '{ty}'
+rhs = [
+  { rhs = ['Int'], act = '{lhs} = {piece}1;' },
+  { rhs = ['LPar', 'Ints', 'RPar'], act = '{lhs} = "(" + {rhs}2 + ")";' },
+]
+
+[[production]]
+lhs = 'Ints'
+ty = '{ty}'
+rhs = [
+  { rhs = ['Int'], act = '{lhs} = {piece}1;' },
+  { rhs = ['Ints', 'Comma', 'Int'], act = '{lhs} = {rhs}1 + "," + {piece}3;' },
+]
+"#;
+
+// the last one has an inconsistent dedent, which is an `_Err` token
+const INDENT_INPUT: [&str; 3] = ["a: 1\n# comment\n\nb:\n  c: (1,\n2, 3)\n  d:\n    e: 4\nf: 5", "a:\n  b: 1\n", "a:\n    b: 1\n  c: 2"];
+const INDENT_OUTPUT: &str = "a=1;b{c=(1,2,3);d{e=4;}}f=5;\na{b=1;}\n1  3 3\n";
+
+#[test]
+fn indent_cpp() {
+  assert_eq!(cpp("indent", &grammar(INDENT_LEXICAL, INDENT_PRODUCTION, true), &INDENT_INPUT), INDENT_OUTPUT);
+}
+
+#[test]
+fn indent_java() {
+  assert_eq!(java("indent", &grammar(INDENT_LEXICAL, INDENT_PRODUCTION, false), &INDENT_INPUT), INDENT_OUTPUT);
+}
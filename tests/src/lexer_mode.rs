@@ -0,0 +1,68 @@
+use parser_macros::lalr1;
+
+// Test lexer modes with nested block comments and strings
+
+#[allow(unused)]
+struct Parser;
+
+#[lalr1(Expr)]
+#[lex = r#"
+priority = []
+
+[lexical]
+'/\*' = '_Eps'
+'"' = 'StrBegin'
+'[a-z]+' = 'Id'
+'\s+' = '_Eps'
+
+[lexer_mode._Init.push]
+'/\*' = 'Comment'
+'"' = 'Str'
+
+[lexer_mode.Comment]
+pop = ['\*/']
+lexical = { '/\*' = '_Eps', '\*/' = '_Eps', '[^*/]+|\*|/' = '_Eps' }
+push = { '/\*' = 'Comment' }
+
+[lexer_mode.Str]
+lexical = { '[^"\\$]+' = 'StrPiece', '\\.' = 'StrEscape', '"' = 'StrEnd', '\$\{' = 'InterpBegin' }
+push = { '\$\{' = 'Interp' }
+pop = ['"']
+
+[lexer_mode.Interp]
+lexical = { '[a-z]+' = 'Id', '\}' = 'InterpEnd', '\s+' = '_Eps' }
+pop = ['\}']
+"#]
+impl Parser {
+  #[rule = "Expr -> Id"]
+  fn expr(_: Token) -> () {} // not using Parser in this test
+}
+
+fn assert(t: Token, kind: TokenKind, piece: &[u8]) {
+  assert_eq!(t.kind, kind);
+  assert_eq!(t.piece, piece);
+}
+
+#[test]
+fn lexer_mode() {
+  use TokenKind::*;
+  let mut l = Lexer::new(b"a /* b /* c */ d */ e");
+  assert(l.next(), Id, b"a");
+  assert(l.next(), Id, b"e");
+  assert(l.next(), _Eof, b"");
+  assert!(l.mode.is_empty());
+
+  let mut l = Lexer::new(br#"x "a\"b${ y }c" z"#);
+  assert(l.next(), Id, b"x");
+  assert(l.next(), StrBegin, b"\"");
+  assert(l.next(), StrPiece, b"a");
+  assert(l.next(), StrEscape, b"\\\"");
+  assert(l.next(), StrPiece, b"b");
+  assert(l.next(), InterpBegin, b"${");
+  assert(l.next(), Id, b"y");
+  assert(l.next(), InterpEnd, b"}");
+  assert(l.next(), StrPiece, b"c");
+  assert(l.next(), StrEnd, b"\"");
+  assert(l.next(), Id, b"z");
+  assert(l.next(), _Eof, b"");
+}
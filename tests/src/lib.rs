@@ -1,7 +1,60 @@
-#![feature(proc_macro_hygiene)]
+#[cfg(test)]
+mod build;
+#[cfg(test)]
+mod c;
+#[cfg(test)]
+mod conflict;
+#[cfg(test)]
+mod cpp;
+#[cfg(test)]
+mod definitions;
+#[cfg(test)]
+mod derive;
+#[cfg(test)]
+mod fallback;
+#[cfg(test)]
+mod fallible;
+#[cfg(test)]
+mod go;
+#[cfg(test)]
+mod indent;
 #[cfg(test)]
 mod lalr1;
 #[cfg(test)]
+mod lalr1_direct;
+#[cfg(test)]
 mod ll1;
 #[cfg(test)]
-mod literal_string;
\ No newline at end of file
+mod ll1_cpp_java;
+#[cfg(test)]
+mod ll1_rd;
+#[cfg(test)]
+mod lex_check;
+#[cfg(test)]
+mod lexer_cpp_java;
+#[cfg(test)]
+mod lexer_mode;
+#[cfg(test)]
+mod locations;
+#[cfg(test)]
+mod literal_string;
+#[cfg(test)]
+mod model;
+#[cfg(test)]
+mod parser_state;
+#[cfg(test)]
+mod placeholder;
+#[cfg(test)]
+mod python;
+#[cfg(test)]
+mod term_value;
+#[cfg(test)]
+mod token;
+#[cfg(test)]
+mod ts;
+#[cfg(test)]
+mod typedef;
+#[cfg(test)]
+mod ui;
+#[cfg(test)]
+mod unicode;
\ No newline at end of file
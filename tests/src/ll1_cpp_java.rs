@@ -0,0 +1,125 @@
+// Generate table-driven LL(1) calculators for C++ and Java, and run them with `g++` and `javac`/`java`
+
+use parser_gen::*;
+use std::{fs, process::Command};
+
+// without left recursion, `a - b + c` is computed as `a + (-b + c)`, so the tails return the sum of the remaining terms
+// the value of lhs is `lhs`, and the value of the n-th rhs symbol is `rhs` followed by n
+fn grammar(include: &str, ty: &str, int: &str, lhs: &str, rhs: &str) -> String {
+  format!(r#"
+include = '{include}'
+priority = []
+start = 'Expr'
+
+[lexical]
+'\(' = 'LParen'
+'\)' = 'RParen'
+'\+' = 'Add'
+'-' = 'Sub'
+'\*' = 'Mul'
+'\d+' = 'IntConst'
+'\s+' = '_Eps'
+
+[[production]]
+lhs = 'Expr'
+ty = '{ty}'
+rhs = [{{ rhs = ['Term', 'ExprTail'], act = '{lhs} = {rhs}1 + {rhs}2;' }}]
+
+[[production]]
+lhs = 'ExprTail'
+ty = '{ty}'
+rhs = [
+  {{ rhs = ['Add', 'Term', 'ExprTail'], act = '{lhs} = {rhs}2 + {rhs}3;' }},
+  {{ rhs = ['Sub', 'Term', 'ExprTail'], act = '{lhs} = -{rhs}2 + {rhs}3;' }},
+  {{ rhs = [], act = '{lhs} = 0;' }},
+]
+
+[[production]]
+lhs = 'Term'
+ty = '{ty}'
+rhs = [{{ rhs = ['Factor', 'TermTail'], act = '{lhs} = {rhs}1 * {rhs}2;' }}]
+
+[[production]]
+lhs = 'TermTail'
+ty = '{ty}'
+rhs = [
+  {{ rhs = ['Mul', 'Factor', 'TermTail'], act = '{lhs} = {rhs}2 * {rhs}3;' }},
+  {{ rhs = [], act = '{lhs} = 1;' }},
+]
+
+[[production]]
+lhs = 'Factor'
+ty = '{ty}'
+rhs = [
+  {{ rhs = ['LParen', 'Expr', 'RParen'], act = '{lhs} = {rhs}2;' }},
+  {{ rhs = ['IntConst'], act = '{lhs} = {int};' }},
+]
+"#, include = include, ty = ty, int = int, lhs = lhs, rhs = rhs)
+}
+
+fn gen(lang: Lang, toml: &str) -> String {
+  let raw = toml::from_str(toml).unwrap();
+  let mut cfg = Config {
+    on_conflict: |c| panic!("{}", c),
+    ..Config::new(lang, Vec::new())
+  };
+  work(raw, PGAlgo::LL1, &mut cfg).unwrap();
+  String::from_utf8(cfg.code_output).unwrap()
+}
+
+fn dir(name: &str) -> std::path::PathBuf {
+  let dir = std::env::temp_dir().join(format!("parser_gen_ll1_{}_{}", name, std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  dir
+}
+
+const INPUT: [&str; 4] = ["1 + 2 * 3", "10 - 2 * (3 + 1) - 1 + 5", "((7))", "1 + * 2"];
+const OUTPUT: &str = "7\n6\n7\n* 1 5\n";
+
+#[test]
+fn cpp() {
+  let code = gen(Lang::Cpp, &grammar("#include <string>", "int", "std::stoi(std::string(_1.piece))", "__", "_"));
+  let dir = dir("cpp");
+  let driver = format!(r#"
+#include <cstdio>
+
+int main() {{
+  for (const char *s : {{"{}"}}) {{
+    Lexer lexer(s);
+    auto res = Parser{{}}.parse(lexer);
+    if (auto v = std::get_if<int>(&res)) {{ printf("%d\n", *v); }}
+    else {{ Token t = std::get<Token>(res); printf("%.*s %u %u\n", (int)t.piece.size(), t.piece.data(), t.line, t.col); }}
+  }}
+}}
+"#, INPUT.join("\", \""));
+  fs::write(dir.join("calc.cpp"), code + &driver).unwrap();
+  let out = Command::new("g++").current_dir(&dir).args(&["-std=c++17", "-o", "calc", "calc.cpp"]).output().expect("failed to run g++");
+  assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+  let out = Command::new(dir.join("calc")).output().unwrap();
+  assert!(out.status.success());
+  assert_eq!(String::from_utf8(out.stdout).unwrap(), OUTPUT);
+}
+
+#[test]
+fn java() {
+  let code = gen(Lang::Java, &grammar("", "int", "Integer.parseInt($1.piece)", "$.$", "$"));
+  let dir = dir("java");
+  let driver = format!(r#"
+public class Main {{
+  public static void main(String[] args) {{
+    for (String s : new String[]{{"{}"}}) {{
+      Parser.Result r = new Parser().parse(new Parser.Lexer(s.getBytes()));
+      if (r.err == null) {{ System.out.println(r.ok); }}
+      else {{ System.out.println(r.err.piece + " " + r.err.line + " " + r.err.col); }}
+    }}
+  }}
+}}
+"#, INPUT.join("\", \""));
+  fs::write(dir.join("Parser.java"), code).unwrap();
+  fs::write(dir.join("Main.java"), driver).unwrap();
+  let out = Command::new("javac").current_dir(&dir).args(&["Parser.java", "Main.java"]).output().expect("failed to run javac");
+  assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+  let out = Command::new("java").current_dir(&dir).args(&["-cp", ".", "Main"]).output().expect("failed to run java");
+  assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+  assert_eq!(String::from_utf8(out.stdout).unwrap(), OUTPUT);
+}
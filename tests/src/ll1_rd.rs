@@ -0,0 +1,89 @@
+use parser_macros::ll1;
+
+// the same grammar as ll1.rs, but using the generated recursive descent parser, so no `_parse` is needed
+
+struct Parser;
+
+pub enum Op { Add, Sub, Mul, Div, Mod }
+
+#[ll1(Expr)]
+#[recursive_descent]
+#[lex_path = "tests/src/lex.toml"]
+impl Parser {
+  #[rule = "Expr -> Term1 Expr1"]
+  fn r0(mut t: i32, remain: Vec<(Op, i32)>) -> i32 {
+    for (op, i) in remain.into_iter().rev() {
+      match op {
+        Op::Add => t += i,
+        Op::Sub => t -= i,
+        _ => unreachable!(),
+      }
+    }
+    t
+  }
+
+  #[rule = "Expr1 -> Add Term1 Expr1"]
+  fn r1(_op: Token, t: i32, mut remain: Vec<(Op, i32)>) -> Vec<(Op, i32)> {
+    remain.push((Op::Add, t));
+    remain
+  }
+
+  #[rule = "Expr1 -> Sub Term1 Expr1"]
+  fn r2(_op: Token, t: i32, mut remain: Vec<(Op, i32)>) -> Vec<(Op, i32)> {
+    remain.push((Op::Sub, t));
+    remain
+  }
+
+  #[rule = "Expr1 ->"]
+  fn r3() -> Vec<(Op, i32)> { vec![] }
+
+  #[rule = "Term1 -> Term2 Expr2"]
+  fn r4(mut t: i32, remain: Vec<(Op, i32)>) -> i32 {
+    for (op, i) in remain.into_iter().rev() {
+      match op {
+        Op::Mul => t *= i,
+        Op::Div => t /= i,
+        Op::Mod => t %= i,
+        _ => unreachable!(),
+      }
+    }
+    t
+  }
+
+  #[rule = "Expr2 -> Mul Term2 Expr2"]
+  fn r5(_op: Token, t: i32, mut remain: Vec<(Op, i32)>) -> Vec<(Op, i32)> {
+    remain.push((Op::Mul, t));
+    remain
+  }
+
+  #[rule = "Expr2 -> Div Term2 Expr2"]
+  fn r6(_op: Token, t: i32, mut remain: Vec<(Op, i32)>) -> Vec<(Op, i32)> {
+    remain.push((Op::Div, t));
+    remain
+  }
+
+  #[rule = "Expr2 -> Mod Term2 Expr2"]
+  fn r7(_op: Token, t: i32, mut remain: Vec<(Op, i32)>) -> Vec<(Op, i32)> {
+    remain.push((Op::Mod, t));
+    remain
+  }
+
+  #[rule = "Expr2 ->"]
+  fn r8() -> Vec<(Op, i32)> { vec![] }
+
+  #[rule = "Term2 -> IntLit"]
+  fn r9(i: Token) -> i32 { std::str::from_utf8(i.piece).unwrap().parse().unwrap() }
+
+  #[rule = "Term2 -> Sub Term2"]
+  fn r10(i: Token, r: i32) -> i32 { -r }
+
+  #[rule = "Term2 -> LPar Expr RPar"]
+  fn r11(_l: Token, x: i32, _r: Token) -> i32 { x }
+}
+
+#[test]
+fn ll1_rd() {
+  assert_eq!(Parser.parse(&mut Lexer::new(b"1 - 2 * (3 + 4 * 5 / 6) + -7 * -9 % 10")), Ok(-8));
+  assert_eq!(Parser.parse(&mut Lexer::new(b"1 + 2)")).map_err(|t| t.kind), Err(TokenKind::RPar));
+  assert_eq!(Parser.parse(&mut Lexer::new(b"(1 + 2")).map_err(|t| t.kind), Err(TokenKind::_Eof));
+}
\ No newline at end of file
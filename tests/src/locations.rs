@@ -0,0 +1,69 @@
+// Test locations: a method argument of type `Span` is the location of lhs, an empty production is located at the lookahead
+
+// the same parser under the attributes of each kind of parser: table-driven LR, directly-coded LR and recursive descent
+macro_rules! parser {
+  ($name: ident, $algo: ident $(, $attr: ident)*) => {
+    mod $name {
+      use parser_macros::$algo;
+
+      pub struct Parser;
+
+      #[$algo(Expr)]
+      $(#[$attr])*
+      #[lex_path = "tests/src/locations.toml"]
+      impl Parser {
+        #[rule = "Expr -> Int Rest"]
+        fn expr(_i: Token, mut r: Vec<Span>, s: Span) -> Vec<Span> {
+          r.push(s);
+          r
+        }
+        #[rule = "Rest ->"]
+        fn rest0(s: Span) -> Vec<Span> { vec![s] }
+        #[rule = "Rest -> Add Expr"]
+        fn rest1(_a: Token, e: Vec<Span>) -> Vec<Span> { e }
+      }
+    }
+  };
+}
+
+parser!(table, lalr1);
+parser!(direct, lalr1, direct_lr);
+parser!(rd, ll1, recursive_descent);
+
+macro_rules! check {
+  ($m: ident) => {{
+    use $m::*;
+    let res = Parser.parse(&mut Lexer::new(b"1 + 23\n+ 4")).unwrap();
+    assert_eq!(res.iter().map(|s| (s.lo, s.hi, s.line, s.col)).collect::<Vec<_>>(),
+      [(10, 10, 2, 4), (9, 10, 2, 3), (4, 10, 1, 5), (0, 10, 1, 1)]);
+  }};
+}
+
+#[test]
+fn locations() {
+  check!(table);
+  check!(direct);
+  check!(rd);
+}
+
+#[test]
+fn with_indent() {
+  use parser_gen::*;
+  let raw = toml::from_str(r#"
+include = ''
+priority = []
+start = 'Expr'
+locations = true
+indent = { indent = 'Indent', dedent = 'Dedent', newline = 'Newline' }
+
+[lexical]
+'\d+' = 'Int'
+
+[[production]]
+lhs = 'Expr'
+ty = 'i32'
+rhs = [{ rhs = ['Int'], act = '0' }]
+"#).unwrap();
+  let e = work(raw, PGAlgo::LALR1, &mut Config::new(Lang::Rs, Vec::new())).unwrap_err();
+  assert!(e.to_string().contains("locations are not supported with indentation"));
+}
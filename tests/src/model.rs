@@ -0,0 +1,55 @@
+// Export the data model of `parser-gen/examples/calc_py.toml` as JSON, and render it with a tera template
+
+use parser_gen::*;
+use serde_json::Value;
+
+fn gen(lang: Lang, template: &str) -> String {
+  let raw = toml::from_str(include_str!("../../parser-gen/examples/calc_py.toml")).unwrap();
+  let mut cfg = Config {
+    template,
+    ..Config::new(lang, Vec::new())
+  };
+  work(raw, PGAlgo::LALR1, &mut cfg).unwrap();
+  String::from_utf8(cfg.code_output).unwrap()
+}
+
+#[test]
+fn json() {
+  let m: Value = serde_json::from_str(&gen(Lang::Json, "")).unwrap();
+  let terms = m["terms"].as_array().unwrap();
+  assert_eq!(terms.iter().take(3).map(|t| t["name"].as_str().unwrap()).collect::<Vec<_>>(), ["_Eps", "_Eof", "_Err"]);
+  let nt = m["nt"].as_array().unwrap();
+  assert_eq!(nt[0]["name"], "Expr");
+  assert_eq!(nt[0]["ty"], "int");
+  // 8 productions of `Expr`, and the augmented one
+  let prod = m["prod"].as_array().unwrap();
+  assert_eq!(prod.len(), 9);
+  let int = &prod[7];
+  assert_eq!(int["act"], "$$ = int($1.piece)");
+  assert_eq!(int["rhs"][0]["name"], "IntConst");
+  assert_eq!(int["rhs"][0]["is_term"], true);
+  let ph = int["placeholders"].as_array().unwrap();
+  assert_eq!((&ph[0]["start"], &ph[0]["end"], &ph[0]["index"]), (&Value::from(0), &Value::from(2), &Value::Null));
+  assert_eq!((&ph[1]["start"], &ph[1]["end"], &ph[1]["index"]), (&Value::from(9), &Value::from(11), &Value::from(1)));
+  let (action, goto) = (m["action"].as_array().unwrap(), m["goto"].as_array().unwrap());
+  assert_eq!(action.len(), goto.len());
+  assert!(action.iter().all(|row| row.as_array().unwrap().len() == terms.len()));
+  assert!(goto.iter().all(|row| row.as_array().unwrap().len() == nt.len()));
+  // exactly one accept entry
+  assert_eq!(action.iter().flat_map(|row| row.as_array().unwrap()).filter(|x| x.as_u64().unwrap() & 3 == 2).count(), 1);
+  let dfa = &m["dfa"];
+  assert_eq!(dfa["ec"].as_array().unwrap().len(), 256);
+  assert_eq!(dfa["acc"].as_array().unwrap().len(), dfa["edge"].as_array().unwrap().len());
+  // the conflicts of the ambiguous expression grammar are all solved by priority, so none is reported
+  assert!(m["conflict"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn template() {
+  let path = std::env::temp_dir().join("parser_gen_model_test.tera");
+  std::fs::write(&path, "{% for t in terms %}{{ t.name }} {% endfor %}\n{% for p in prod %}{{ p.lhs }}:{{ p.rhs | length }} {% endfor %}").unwrap();
+  let out = gen(Lang::Template, path.to_str().unwrap());
+  let mut lines = out.lines();
+  assert!(lines.next().unwrap().starts_with("_Eps _Eof _Err "));
+  assert_eq!(lines.next().unwrap(), "0:3 0:3 0:3 0:3 0:3 0:2 0:3 0:1 1:1 ");
+}
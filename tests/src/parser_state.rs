@@ -0,0 +1,49 @@
+use parser_macros::lalr1;
+use std::collections::HashMap;
+
+// Test actions taking `&mut self` / `&self` to access the fields of the parser, and methods with several #[rule]s
+
+#[derive(Default)]
+struct Parser {
+  vars: HashMap<Vec<u8>, i32>,
+}
+
+#[lalr1(Prog)]
+#[token(Add = r"\+", prec = 1)]
+#[token(Sub = "-", prec = 1)]
+#[token(Mul = r"\*", prec = 2)]
+#[token(Let = "let")]
+#[token(Eq = "=")]
+#[token(Semi = ";")]
+#[token(Int = r"\d+")]
+#[token(Id = "[a-z]+")]
+#[token(_Eps = r"\s+")]
+impl Parser {
+  #[rule = "Prog -> Stmts Expr"]
+  fn prog(_s: (), e: i32) -> i32 { e }
+  #[rule = "Stmts -> Stmts Stmt"]
+  fn stmts1(_s: (), _s1: ()) {}
+  #[rule = "Stmts ->"]
+  fn stmts0() {}
+  #[rule = "Stmt -> Let Id Eq Expr Semi"]
+  fn stmt(&mut self, _l: Token, id: Token, _e: Token, v: i32, _s: Token) {
+    self.vars.insert(id.piece.to_vec(), v);
+  }
+  #[rule = "Expr -> Expr Add Expr"]
+  #[rule = "Expr -> Expr Sub Expr"]
+  #[rule = "Expr -> Expr Mul Expr"]
+  fn bin(l: i32, op: Token, r: i32) -> i32 {
+    match op.kind { TokenKind::Add => l + r, TokenKind::Sub => l - r, _ => l * r }
+  }
+  #[rule = "Expr -> Id"]
+  fn var(&self, id: Token) -> i32 { self.vars[id.piece] }
+  #[rule = "Expr -> Int"]
+  fn int(i: Token) -> i32 { std::str::from_utf8(i.piece).unwrap().parse().unwrap() }
+}
+
+#[test]
+fn parser_state() {
+  let mut p = Parser::default();
+  assert_eq!(p.parse(&mut Lexer::new(b"let x = 2 * 3; let y = x - 1; x * y + 1")), Ok(31));
+  assert_eq!(p.vars[&b"y"[..]], 5);
+}
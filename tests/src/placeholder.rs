@@ -0,0 +1,44 @@
+use common::{placeholders, Placeholder::{self, *}, Quotes};
+
+// Test the bison-style placeholders `$$`, `$n`, `$name`, `@$`, `@n` in actions
+
+fn ph<'a>(act: &'a str, rhs: &[&str], loc: bool) -> Result<Vec<(&'a str, Placeholder)>, String> {
+  ph_in(act, rhs, loc, Quotes::Rust)
+}
+
+fn ph_in<'a>(act: &'a str, rhs: &[&str], loc: bool, quotes: Quotes) -> Result<Vec<(&'a str, Placeholder)>, String> {
+  placeholders(act, rhs, loc, quotes).map(|v| v.into_iter().map(|(s, e, p)| (&act[s..e], p)).collect())
+}
+
+#[test]
+fn placeholder() {
+  let rhs = ["Expr", "Add", "Term"];
+  assert_eq!(ph("$$ = $1 + $Term;", &rhs, false).unwrap(), [("$$", Val(None)), ("$1", Val(Some(1))), ("$Term", Val(Some(3)))]);
+  assert_eq!(ph("Node { loc: @$, op: @2, l: $1 }", &rhs, true).unwrap(), [("@$", Loc(None)), ("@2", Loc(Some(2))), ("$1", Val(Some(1)))]);
+  // `@` is not a placeholder without locations, and placeholders in string literals and `$.$` (java) are ignored
+  assert_eq!(ph(r#"x @ 1; println!("$1 \" $2"); '"'; $.$ = $3"#, &rhs, false).unwrap(), [("$3", Val(Some(3)))]);
+  assert!(ph("$4", &rhs, false).unwrap_err().contains("out of range"));
+  assert!(ph("$0", &rhs, false).unwrap_err().contains("out of range"));
+  assert!(ph("@4", &rhs, true).unwrap_err().contains("out of range"));
+  assert!(ph("$Expr", &["Expr", "Add", "Expr"], false).unwrap_err().contains("ambiguous"));
+  // `$name` is only a placeholder if `name` is in rhs, so java variables like `$tmp` are left as is
+  assert_eq!(ph_in("int $tmp = $Term; $.$ = $tmp;", &rhs, false, Quotes::C).unwrap(), [("$Term", Val(Some(3)))]);
+  // rust lifetimes are not char literals, c char literals can be longer than one char
+  assert_eq!(ph("fn f<'a>(x: &'a str) -> &'a str { $1 }", &rhs, false).unwrap(), [("$1", Val(Some(1)))]);
+  assert_eq!(ph_in("'$1' + '\\'' + $2", &rhs, false, Quotes::C).unwrap(), [("$2", Val(Some(2)))]);
+  // python, typescript and go also have `'...'` and `` `...` `` strings
+  assert_eq!(ph_in("f'{$1}' + `$2` + $3", &rhs, false, Quotes::Script).unwrap(), [("$3", Val(Some(3)))]);
+  assert_eq!(ph_in("`$2` + $3", &rhs, false, Quotes::C).unwrap(), [("$2", Val(Some(2))), ("$3", Val(Some(3)))]);
+}
+
+// generated by `Builder::new("src/placeholder.toml").generate()` in `tests/build.rs`, whose actions use `$$`, `$1`, `$Int` and `@3`
+#[allow(warnings)]
+mod sum {
+  include!(concat!(env!("OUT_DIR"), "/placeholder.rs"));
+}
+
+#[test]
+fn rust() {
+  let (sum, s) = sum::Parser {}.parse(&mut sum::Lexer::new(b"1 + 22 + 3")).unwrap();
+  assert_eq!((sum, &*s), (26, "$1\"4$1\"9"));
+}
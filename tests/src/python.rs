@@ -0,0 +1,25 @@
+// Generate the python calculator in `parser-gen/examples` and run it with `python3`
+
+use parser_gen::*;
+use std::process::Command;
+
+const DRIVER: &str = r#"
+p = Parser()
+print(p.parse(Lexer('1 + 2 * 3')))
+print(p.parse(Lexer('(1 + 2) * -3 % 5')))
+try:
+  p.parse(Lexer('1 + * 2'))
+except ParseError as e:
+  print(e.token.kind.name, e.token.piece, e.token.line, e.token.col)
+"#;
+
+#[test]
+fn calc() {
+  let raw = toml::from_str(include_str!("../../parser-gen/examples/calc_py.toml")).unwrap();
+  let mut cfg = Config::new(Lang::Python, Vec::new());
+  work(raw, PGAlgo::LALR1, &mut cfg).unwrap();
+  let code = String::from_utf8(cfg.code_output).unwrap() + DRIVER;
+  let out = Command::new("python3").arg("-c").arg(&code).output().expect("failed to run python3");
+  assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+  assert_eq!(String::from_utf8(out.stdout).unwrap(), "7\n1\nMul * 1 5\n");
+}
@@ -0,0 +1,55 @@
+// Test terms with values: actions receive the converted values instead of tokens, and a failed conversion is reported as an `_Err` token
+
+#[derive(Debug, PartialEq)]
+pub enum Item { Int(i32), Name(String) }
+
+// the same parser under the attributes of each kind of parser: table-driven LR, directly-coded LR and recursive descent
+macro_rules! parser {
+  ($name: ident, $algo: ident $(, $attr: ident)*) => {
+    mod $name {
+      use parser_macros::$algo;
+      use super::Item;
+
+      pub struct Parser;
+
+      #[$algo(List)]
+      $(#[$attr])*
+      #[lex_path = "tests/src/term_value.toml"]
+      impl Parser {
+        #[rule = "List ->"]
+        fn list0() -> Vec<Item> { Vec::new() }
+        #[rule = "List -> Item List"]
+        fn list1(i: Item, mut l: Vec<Item>) -> Vec<Item> {
+          l.insert(0, i);
+          l
+        }
+        #[rule = "Item -> Int"]
+        fn item_int(i: i32) -> Item { Item::Int(i) }
+        #[rule = "Item -> Name"]
+        fn item_name(n: String) -> Item { Item::Name(n) }
+      }
+    }
+  };
+}
+
+parser!(table, lalr1);
+parser!(direct, lalr1, direct_lr);
+parser!(rd, ll1, recursive_descent);
+
+macro_rules! check {
+  ($m: ident) => {{
+    use $m::*;
+    assert_eq!(Parser.parse(&mut Lexer::new(b"1 a 23 bc")).unwrap(),
+      [Item::Int(1), Item::Name("a".to_owned()), Item::Int(23), Item::Name("bc".to_owned())]);
+    // out of the range of i32
+    let e = Parser.parse(&mut Lexer::new(b"1 a 99999999999")).err().unwrap();
+    assert_eq!((e.kind, e.piece, e.col), (TokenKind::_Err, &b"99999999999"[..], 5));
+  }};
+}
+
+#[test]
+fn term_value() {
+  check!(table);
+  check!(direct);
+  check!(rd);
+}
@@ -0,0 +1,64 @@
+use parser_macros::lalr1;
+
+// Test #[token]: the lexer of `tests/src/lalr1.rs` declared in attributes, the larger `prec` is, the tighter a term binds
+
+struct Parser;
+
+#[lalr1(Expr)]
+#[token(Add = r"\+", prec = 1, assoc = "left")]
+#[token(Sub = "-", prec = 1, assoc = "left")]
+#[token(Mul = r"\*", prec = 2, assoc = "left")]
+#[token(Div = "/", prec = 2)]
+#[token(UMinus, prec = 3, assoc = "no_assoc")]
+#[token(LPar = r"\(")]
+#[token(RPar = r"\)")]
+#[token(IntLit = r"\d+")]
+#[token(_Eps = r"\s+")]
+impl Parser {
+  #[rule = "Expr -> Expr Add Expr"]
+  fn expr_add(l: i32, _op: Token, r: i32) -> i32 { l + r }
+  #[rule = "Expr -> Expr Sub Expr"]
+  fn expr_sub(l: i32, _op: Token, r: i32) -> i32 { l - r }
+  #[rule = "Expr -> Expr Mul Expr"]
+  fn expr_mul(l: i32, _op: Token, r: i32) -> i32 { l * r }
+  #[rule = "Expr -> Expr Div Expr"]
+  fn expr_div(l: i32, _op: Token, r: i32) -> i32 { l / r }
+  #[rule = "Expr -> Sub Expr"]
+  #[prec = "UMinus"]
+  fn expr_neg(_op: Token, r: i32) -> i32 { -r }
+  #[rule = "Expr -> LPar Expr RPar"]
+  fn expr_paren(_l: Token, i: i32, _r: Token) -> i32 { i }
+  #[rule = "Expr -> IntLit"]
+  fn expr_int(i: Token) -> i32 { std::str::from_utf8(i.piece).unwrap().parse().unwrap() }
+}
+
+#[test]
+fn token() {
+  assert_eq!(Parser.parse(&mut Lexer::new(b"1 - 2 * (3 + 4 * 5 / 6) + -7 * -9")), Ok(52));
+}
+
+// the assoc of a row can be given by any of its terms, here `Sub` makes the row of `Add` and `Sub` right associative
+mod right {
+  use parser_macros::lalr1;
+
+  struct Parser;
+
+  #[lalr1(Expr)]
+  #[token(Add = r"\+", prec = 1)]
+  #[token(Sub = "-", prec = 1, assoc = "right")]
+  #[token(IntLit = r"\d+")]
+  #[token(_Eps = r"\s+")]
+  impl Parser {
+    #[rule = "Expr -> Expr Add Expr"]
+    fn expr_add(l: i32, _op: Token, r: i32) -> i32 { l + r }
+    #[rule = "Expr -> Expr Sub Expr"]
+    fn expr_sub(l: i32, _op: Token, r: i32) -> i32 { l - r }
+    #[rule = "Expr -> IntLit"]
+    fn expr_int(i: Token) -> i32 { std::str::from_utf8(i.piece).unwrap().parse().unwrap() }
+  }
+
+  #[test]
+  fn right() {
+    assert_eq!(Parser.parse(&mut Lexer::new(b"1 - 2 + 3")), Ok(-4));
+  }
+}
@@ -0,0 +1,38 @@
+// Generate the TypeScript calculator in `parser-gen/examples`, compile it with `tsc` and run it with `node`
+
+use parser_gen::*;
+use std::{fs, process::Command};
+
+const DRIVER: &str = r#"
+import { Lexer, Parser, Result, TokenKind } from './calc';
+
+function show(r: Result): string {
+  return r.ok ? r.value.toString() : `${r.token.kind === TokenKind.Mul} ${r.token.piece} ${r.token.line} ${r.token.col}`;
+}
+
+const p = new Parser();
+console.log(show(p.parse(new Lexer('1 + 2 * 3'))));
+console.log(show(p.parse(new Lexer('(1 + 2) * -3 % 5'))));
+console.log(show(p.parse(new Lexer('7 / 2 - 10'))));
+console.log(show(p.parse(new Lexer(new TextEncoder().encode('1 +\n * 2')))));
+"#;
+
+// unlike python3 and cc, tsc is installed from npm, so it is only run with `--ignored`
+#[test]
+#[ignore = "needs tsc/node"]
+fn calc() {
+  let raw = toml::from_str(include_str!("../../parser-gen/examples/calc_ts.toml")).unwrap();
+  let mut cfg = Config::new(Lang::TypeScript, Vec::new());
+  work(raw, PGAlgo::LALR1, &mut cfg).unwrap();
+  let dir = std::env::temp_dir().join(format!("parser_gen_ts_{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(dir.join("calc.ts"), cfg.code_output).unwrap();
+  fs::write(dir.join("main.ts"), DRIVER).unwrap();
+  let out = Command::new("tsc").current_dir(&dir)
+    .args(&["--strict", "--target", "es2020", "--module", "commonjs", "--lib", "es2020,dom", "main.ts"]).output().expect("failed to run tsc");
+  assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stdout));
+  let out = Command::new("node").current_dir(&dir).arg("main.js").output().expect("failed to run node");
+  assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+  // `/` is truncated by `Math.trunc` in the action, and `%` keeps the sign of the dividend like C
+  assert_eq!(String::from_utf8(out.stdout).unwrap(), "7\n-4\n-7\ntrue * 2 2\n");
+}
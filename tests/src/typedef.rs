@@ -0,0 +1,55 @@
+use parser_macros::lalr1;
+
+// Test the "typedef-name" problem: the lexer classifies identifiers according to the typedefs reduced by the parser
+
+struct Parser;
+
+fn s(t: Token) -> String { std::str::from_utf8(t.piece).unwrap().to_owned() }
+
+#[lalr1(Stmts)]
+#[lex = r#"
+# TypeName is never produced by a lexical rule, declare it here
+priority = [{ assoc = 'left', terms = ['TypeName'] }]
+lexer_field = "pub types: std::collections::HashSet<&'l [u8]>,"
+lexer_action = '''
+if last_acc == TokenKind::Ident && self.types.contains(piece) { last_acc = TokenKind::TypeName; }
+'''
+
+[lexical]
+'typedef' = 'Typedef'
+'int' = 'Int'
+'\*' = 'Mul'
+';' = 'Semi'
+'[a-zA-Z]+' = 'Ident'
+'\s+' = '_Eps'
+"#]
+impl Parser {
+  #[rule = "Stmts ->"]
+  fn stmts0() -> Vec<String> { Vec::new() }
+  #[rule = "Stmts -> Stmts Stmt"]
+  fn stmts1(mut l: Vec<String>, r: String) -> Vec<String> {
+    l.push(r);
+    l
+  }
+  #[rule = "Stmt -> Typedef Type Ident Semi"]
+  fn stmt_typedef(_t: Token, ty: String, name: Token, _s: Token, lexer: &mut Lexer, token: &mut Token) -> String {
+    lexer.types.insert(name.piece);
+    // the lookahead is lexed before this reduction, so it needs to be fixed manually
+    if token.kind == TokenKind::Ident && token.piece == name.piece { token.kind = TokenKind::TypeName; }
+    format!("typedef {} {}", ty, s(name))
+  }
+  #[rule = "Stmt -> Type Mul Ident Semi"]
+  fn stmt_decl(ty: String, _m: Token, name: Token, _s: Token) -> String { format!("decl {} *{}", ty, s(name)) }
+  #[rule = "Stmt -> Ident Mul Ident Semi"]
+  fn stmt_mul(l: Token, _m: Token, r: Token, _s: Token) -> String { format!("mul {} {}", s(l), s(r)) }
+  #[rule = "Type -> Int"]
+  fn type_int(_i: Token) -> String { "int".to_owned() }
+  #[rule = "Type -> TypeName"]
+  fn type_name(t: Token) -> String { s(t) }
+}
+
+#[test]
+fn typedef() {
+  let res = Parser.parse(&mut Lexer::new(b"a * b; typedef int T; T * x; typedef T U; U * y;")).unwrap();
+  assert_eq!(res, ["mul a b", "typedef int T", "decl T *x", "typedef T U", "decl U *y"].iter().map(|x| x.to_string()).collect::<Vec<_>>());
+}
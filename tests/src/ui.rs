@@ -0,0 +1,7 @@
+// Check the compile errors reported by the proc macros, and where they are located, the expected outputs are in `ui/*.stderr`
+// run with `TRYBUILD=overwrite` to update them after the messages change
+
+#[test]
+fn ui() {
+  trybuild::TestCases::new().compile_fail("ui/*.rs");
+}
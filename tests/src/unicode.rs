@@ -0,0 +1,116 @@
+use parser_macros::lalr1;
+use parser_gen::*;
+
+// Test unicode classes, code point columns and tab stops
+
+struct Parser;
+
+#[lalr1(Words)]
+#[lex = r#"
+priority = []
+unicode = true
+tab_width = 4
+
+[lexical]
+'\p{Greek}+' = 'Greek'
+'\p{Latin}+' = 'Latin'
+'\P{L}' = 'Other'
+"#]
+impl Parser {
+  #[rule = "Words ->"]
+  fn words0() -> u32 { 0 }
+  #[rule = "Words -> Words Word"]
+  fn words1(n: u32, w: u32) -> u32 { n + w }
+  #[rule = "Word -> Greek"]
+  fn word_greek(_w: Token) -> u32 { 1 }
+  #[rule = "Word -> Latin"]
+  fn word_latin(_w: Token) -> u32 { 1 }
+  #[rule = "Word -> Other"]
+  fn word_other(_w: Token) -> u32 { 1 }
+}
+
+fn assert(t: Token, kind: TokenKind, piece: &str, col: u32) {
+  assert_eq!((t.kind, t.piece_str(), t.line, t.col), (kind, piece, 1, col));
+}
+
+#[test]
+fn unicode() {
+  use TokenKind::*;
+  // `col` counts code points, and `\t` moves it to the next multiple of 4 (plus 1)
+  let s = "αβx\t😀Ωz\tz";
+  let mut l = Lexer::new(s.as_bytes());
+  assert(l.next(), Greek, "αβ", 1);
+  assert(l.next(), Latin, "x", 3);
+  assert(l.next(), Other, "\t", 4);
+  assert(l.next(), Other, "😀", 5);
+  assert(l.next(), Greek, "Ω", 6);
+  assert(l.next(), Latin, "z", 7);
+  assert(l.next(), Other, "\t", 8);
+  assert(l.next(), Latin, "z", 9);
+  assert(l.next(), _Eof, "", 10);
+  assert_eq!(Parser.parse(&mut Lexer::new(s.as_bytes())).ok(), Some(8));
+  // an incomplete utf-8 sequence is an error token, `piece_str` drops the invalid suffix
+  let t = Lexer::new(b"\xce").next();
+  assert_eq!((t.kind, t.piece, t.piece_str()), (_Err, &b"\xce"[..], ""));
+}
+
+#[test]
+fn class_in_brackets() {
+  let raw = toml::from_str(r#"
+include = ''
+priority = []
+start = 'Id'
+
+[lexical]
+'[\p{L}_]+' = 'Name'
+
+[[production]]
+lhs = 'Id'
+ty = 'int'
+rhs = [{ rhs = ['Name'], act = '' }]
+"#).unwrap();
+  let mut cfg = Config::new(Lang::Json, Vec::new());
+  let e = work(raw, PGAlgo::LALR1, &mut cfg).unwrap_err();
+  assert!(e.to_string().contains("unicode class inside [] is not supported"));
+}
+
+// a quantifier applies to a whole non-ascii char, `.` and `[^...]` match a whole code point
+mod code_point {
+  use parser_macros::lalr1;
+
+  struct Parser;
+
+  #[lalr1(Words)]
+  #[lex = r#"
+priority = []
+unicode = true
+
+[lexical]
+'é+' = 'E'
+'x.' = 'X'
+'[^éx]' = 'Other'
+"#]
+  impl Parser {
+    #[rule = "Words ->"]
+    fn words0() -> u32 { 0 }
+    #[rule = "Words -> Words Word"]
+    fn words1(n: u32, w: u32) -> u32 { n + w }
+    #[rule = "Word -> E"]
+    fn word_e(_w: Token) -> u32 { 1 }
+    #[rule = "Word -> X"]
+    fn word_x(_w: Token) -> u32 { 1 }
+    #[rule = "Word -> Other"]
+    fn word_other(_w: Token) -> u32 { 1 }
+  }
+
+  #[test]
+  fn code_point() {
+    use TokenKind::*;
+    let mut l = Lexer::new("éé©x€€".as_bytes());
+    for &(kind, piece, col) in &[(E, "éé", 1), (Other, "©", 3), (X, "x€", 4), (Other, "€", 6), (_Eof, "", 7)] {
+      let t = l.next();
+      assert_eq!((t.kind, t.piece_str(), t.col), (kind, piece, col));
+    }
+    assert_eq!(Parser.parse(&mut Lexer::new("éé©x€€".as_bytes())).ok(), Some(4));
+  }
+}
@@ -0,0 +1,17 @@
+use parser_macros::lalr1;
+
+struct Parser;
+
+#[lalr1(Expr)]
+#[token(IntLit = r"\d+")]
+#[token(Add = r"\+")]
+// unicode classes can't be used in brackets
+#[token(Name = r"[\p{L}_]+")]
+impl Parser {
+  #[rule = "Expr -> IntLit"]
+  fn expr_int(_i: Token) -> i32 { 0 }
+  #[rule = "Expr -> Expr Add IntLit"]
+  fn expr_add(l: i32, _op: Token, _r: Token) -> i32 { l }
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+// without #[deny_conflicts] the conflict is a deprecation warning located at the rule, which is denied here to check it
+#![deny(deprecated)]
+
+use parser_macros::lalr1;
+
+struct Parser;
+
+#[lalr1(Expr)]
+#[lex = r#"
+priority = []
+
+[lexical]
+'-' = 'Sub'
+'\d+' = 'IntLit'
+"#]
+impl Parser {
+  #[rule = "Expr -> Expr Sub Expr"]
+  fn expr_sub(l: i32, _op: Token, r: i32) -> i32 { l - r }
+  #[rule = "Expr -> IntLit"]
+  fn expr_int(_i: Token) -> i32 { 0 }
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use parser_macros::lalr1;
+
+struct Parser;
+
+#[lalr1(Expr)]
+#[deny_conflicts]
+#[lex = r#"
+priority = []
+
+[lexical]
+'-' = 'Sub'
+'\d+' = 'IntLit'
+"#]
+impl Parser {
+  // without priority, there is a shift-reduce conflict on `Sub`
+  #[rule = "Expr -> Expr Sub Expr"]
+  fn expr_sub(l: i32, _op: Token, r: i32) -> i32 { l - r }
+  #[rule = "Expr -> IntLit"]
+  fn expr_int(_i: Token) -> i32 { 0 }
+}
+
+fn main() {}
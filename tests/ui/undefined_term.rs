@@ -0,0 +1,20 @@
+use parser_macros::lalr1;
+
+struct Parser;
+
+#[lalr1(Expr)]
+#[lex = r#"
+priority = []
+
+[lexical]
+'\d+' = 'IntLit'
+"#]
+impl Parser {
+  #[rule = "Expr -> IntLit"]
+  fn expr_int(_i: Token) -> i32 { 0 }
+  // `Add` is not defined in lex
+  #[rule = "Expr -> Expr Add IntLit"]
+  fn expr_add(l: i32, _op: Token, _r: Token) -> i32 { l }
+}
+
+fn main() {}